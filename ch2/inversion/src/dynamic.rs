@@ -0,0 +1,154 @@
+//! 値の書き換え・位置の入れ替えをしながら転倒数を保つ
+//! - 長さ B ≈ √n のブロックごとに整列済みの値を持つ（平方分割）
+//! - 位置 p の値を変えるとき、p の左で自分より大きい数と右で自分より小さい数を
+//!   変更の前後で数え、差分だけ転倒数を更新する
+//! - 1操作 O(√n log n)
+
+use crate::fenwick::count_inversion_with_fenwick;
+
+/// 転倒数を保ちながら書き換えられる配列
+pub struct DynamicInversions<T> {
+    a: Vec<T>,
+    block: usize,
+    /// sorted[k] = ブロック k の値を昇順に並べたもの
+    sorted: Vec<Vec<T>>,
+    count: i64,
+}
+
+impl<T: Ord + Clone> DynamicInversions<T> {
+    pub fn new(a: Vec<T>) -> Self {
+        let block = ((a.len() as f64).sqrt() as usize).max(1);
+        let sorted = a
+            .chunks(block)
+            .map(|chunk| {
+                let mut values = chunk.to_vec();
+                values.sort();
+                values
+            })
+            .collect();
+        let count = count_inversion_with_fenwick(&a);
+        Self {
+            a,
+            block,
+            sorted,
+            count,
+        }
+    }
+
+    /// 現在の転倒数
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.a
+    }
+
+    /// [l, r) のうち pred を満たす要素の個数
+    /// - sorted_count はブロックの整列済みの値から同じ個数を二分探索で求める
+    fn count_in(
+        &self,
+        l: usize,
+        r: usize,
+        pred: impl Fn(&T) -> bool,
+        sorted_count: impl Fn(&[T]) -> usize,
+    ) -> i64 {
+        let mut count = 0;
+        let mut i = l;
+        while i < r {
+            if i.is_multiple_of(self.block) && i + self.block <= r {
+                // ブロック全体が入る
+                count += sorted_count(&self.sorted[i / self.block]);
+                i += self.block;
+            } else {
+                count += pred(&self.a[i]) as usize;
+                i += 1;
+            }
+        }
+        count as i64
+    }
+
+    /// 位置 p の値 x が関わる転倒の個数
+    fn inversions_at(&self, p: usize, x: &T) -> i64 {
+        let greater_left = self.count_in(
+            0,
+            p,
+            |y| y > x,
+            |values| values.len() - values.partition_point(|y| y <= x),
+        );
+        let smaller_right = self.count_in(
+            p + 1,
+            self.a.len(),
+            |y| y < x,
+            |values| values.partition_point(|y| y < x),
+        );
+        greater_left + smaller_right
+    }
+
+    /// 位置 i の値を value に書き換える
+    pub fn set(&mut self, i: usize, value: T) {
+        let old = self.a[i].clone();
+        self.count -= self.inversions_at(i, &old);
+        self.count += self.inversions_at(i, &value);
+
+        let values = &mut self.sorted[i / self.block];
+        let pos = values
+            .binary_search(&old)
+            .expect("ブロックには元の値がある");
+        values.remove(pos);
+        let pos = values.partition_point(|y| y < &value);
+        values.insert(pos, value.clone());
+        self.a[i] = value;
+    }
+
+    /// 位置 i と j の値を入れ替える
+    pub fn swap(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        let (x, y) = (self.a[i].clone(), self.a[j].clone());
+        self.set(i, y);
+        self.set(j, x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_inversion_with_merge_sort;
+    use rand::Rng;
+
+    fn expected(d: &DynamicInversions<i32>) -> i64 {
+        count_inversion_with_merge_sort(&mut d.as_slice().to_vec())
+    }
+
+    #[test]
+    fn test_dynamic_inversions_1() {
+        let mut d = DynamicInversions::new(vec![2, 3, 8, 6, 1]);
+        assert_eq!(d.count(), 5);
+        d.swap(0, 4);
+        assert_eq!(d.as_slice(), &[1, 3, 8, 6, 2]);
+        assert_eq!(d.count(), 4);
+        d.set(2, 0);
+        assert_eq!(d.count(), 4);
+    }
+
+    #[test]
+    fn test_dynamic_inversions_random() {
+        let mut rng = rand::thread_rng();
+        for n in [1, 2, 3, 10, 37, 200] {
+            let max = rng.gen_range(1..30);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..max)).collect();
+            let mut d = DynamicInversions::new(a);
+            assert_eq!(d.count(), expected(&d));
+            for _ in 0..500 {
+                if rng.gen_bool(0.5) {
+                    d.set(rng.gen_range(0..n), rng.gen_range(0..max));
+                } else {
+                    d.swap(rng.gen_range(0..n), rng.gen_range(0..n));
+                }
+                assert_eq!(d.count(), expected(&d));
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! フェニック木（Binary Indexed Tree）と座標圧縮
+//! - 入力を書き換えずに、O(n log n) で転倒数を数える
+
+/// フェニック木（添字は 0 始まり、内部では 1 始まり）
+pub struct Fenwick {
+    tree: Vec<i64>,
+}
+
+impl Fenwick {
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![0; n + 1],
+        }
+    }
+
+    /// i 番目に delta を足す
+    pub fn add(&mut self, i: usize, delta: i64) {
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            // 最下位の1のビットを足して親へ
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// [0, i) の和
+    pub fn prefix_sum(&self, i: usize) -> i64 {
+        let mut i = i;
+        let mut sum = 0;
+        while i > 0 {
+            sum += self.tree[i];
+            // 最下位の1のビットを消して次の区間へ
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// [l, r) の和
+    pub fn range_sum(&self, l: usize, r: usize) -> i64 {
+        self.prefix_sum(r) - self.prefix_sum(l)
+    }
+
+    /// [0, i] の和が k を超える最小の i（各要素が 0 以上のとき）
+    /// - 0/1 の要素なら「k 番目（0 始まり）の 1 の位置」
+    /// - 該当がなければ要素数を返す
+    pub fn kth(&self, k: i64) -> usize {
+        let n = self.tree.len() - 1;
+        let mut pos = 0;
+        let mut rest = k;
+        // 大きい区間から順に、和が k 以下のままなら進む
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step] <= rest {
+                pos += step;
+                rest -= self.tree[pos];
+            }
+            step >>= 1;
+        }
+        pos
+    }
+}
+
+/// 座標圧縮
+/// - 各要素を「異なる値の中で小さい方から何番目か」（0 始まり）に置き換える
+/// - 戻り値の2つ目は異なる値の個数
+pub fn compress<T: Ord>(a: &[T]) -> (Vec<usize>, usize) {
+    let mut values: Vec<&T> = a.iter().collect();
+    values.sort();
+    values.dedup();
+    let ranks = a
+        .iter()
+        .map(|x| values.binary_search(&x).unwrap())
+        .collect();
+    (ranks, values.len())
+}
+
+/// フェニック木を利用して転倒数を求める
+/// - 左から順に見て、それまでに出た要素のうち自分より大きいものの個数を足していく
+pub fn count_inversion_with_fenwick<T: Ord>(a: &[T]) -> i64 {
+    let (ranks, distinct) = compress(a);
+    let mut fenwick = Fenwick::new(distinct);
+    let mut count = 0;
+    for (seen, &r) in ranks.iter().enumerate() {
+        // seen 個のうち、r 以下のものは転倒にならない
+        count += seen as i64 - fenwick.prefix_sum(r + 1);
+        fenwick.add(r, 1);
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_inversion_with_merge_sort, naive};
+    use rand::Rng;
+
+    #[test]
+    fn test_fenwick() {
+        let mut fenwick = Fenwick::new(5);
+        fenwick.add(0, 3);
+        fenwick.add(2, 5);
+        fenwick.add(4, -1);
+        assert_eq!(fenwick.prefix_sum(0), 0);
+        assert_eq!(fenwick.prefix_sum(3), 8);
+        assert_eq!(fenwick.range_sum(1, 5), 4);
+    }
+
+    #[test]
+    fn test_fenwick_kth() {
+        let mut fenwick = Fenwick::new(6);
+        for i in [1, 2, 4] {
+            fenwick.add(i, 1);
+        }
+        assert_eq!(fenwick.kth(0), 1);
+        assert_eq!(fenwick.kth(1), 2);
+        assert_eq!(fenwick.kth(2), 4);
+        assert_eq!(fenwick.kth(3), 6);
+        assert_eq!(Fenwick::new(0).kth(0), 0);
+    }
+
+    #[test]
+    fn test_compress() {
+        let (ranks, distinct) = compress(&[30, -5, 30, 7]);
+        assert_eq!(ranks, vec![2, 0, 2, 1]);
+        assert_eq!(distinct, 3);
+    }
+
+    #[test]
+    fn test_count_inversion_with_fenwick_1() {
+        let a = vec![2, 3, 8, 6, 1];
+        assert_eq!(count_inversion_with_fenwick(&a), 5);
+        // 入力は書き換えない
+        assert_eq!(a, vec![2, 3, 8, 6, 1]);
+    }
+
+    #[test]
+    fn test_count_inversion_with_fenwick_other_types() {
+        assert_eq!(count_inversion_with_fenwick(&["b", "a", "c", "a"]), 3);
+        assert_eq!(count_inversion_with_fenwick::<i64>(&[]), 0);
+    }
+
+    #[test]
+    fn test_cross_check_random_with_duplicates() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let n = rng.gen_range(0..60);
+            let max = rng.gen_range(1..6);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..max)).collect();
+            let expected = naive(&a);
+            assert_eq!(count_inversion_with_fenwick(&a), expected);
+            assert_eq!(count_inversion_with_merge_sort(&mut a.clone()), expected);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! 2つの順位づけの Kendall の τ
+//! - (x, y) の組を x（同値は y）の順に並べると、y の列の転倒数が不一致の組の数になる
+//!   （Knight の O(n log n) のアルゴリズム）
+//! - 同順位は x の同値・y の同値・両方の同値の組数で補正する（τ-b）
+//! - p 値は同順位を補正した分散による正規近似（両側）
+
+use crate::count_inversion_with_merge_sort;
+use special::erfc;
+use std::cmp::Ordering;
+
+/// Kendall の τ の計算結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kendall {
+    pub n: usize,
+    /// 不一致の組の数（Kendall の τ 距離。どちらかが同順位の組は数えない）
+    pub distance: i64,
+    /// (一致 - 不一致) / 全組数
+    pub tau_a: f64,
+    /// 同順位を補正した τ（一方の列がすべて同順位なら 0/0 になるので None）
+    pub tau_b: Option<f64>,
+    /// 一致 - 不一致 を標準化した値
+    pub z: f64,
+    /// 両側 p 値
+    pub p_value: f64,
+}
+
+/// 整列済みの列を同値の区間に分け、それぞれの長さを返す
+fn tie_lengths<K: PartialEq>(sorted: &[K]) -> Vec<f64> {
+    sorted
+        .chunk_by(|a, b| a == b)
+        .map(|run| run.len() as f64)
+        .collect()
+}
+
+/// 同値の組の数 Σ t(t-1)/2
+fn tied_pairs(ties: &[f64]) -> f64 {
+    ties.iter().map(|t| t * (t - 1.0) / 2.0).sum()
+}
+
+fn without_negative_zero(values: &[f64]) -> Vec<f64> {
+    values
+        .iter()
+        .map(|&v| if v == 0.0 { 0.0 } else { v })
+        .collect()
+}
+
+/// x と y の Kendall の τ を求める
+/// - x[i] と y[i] が同じ項目の順位（値は大小だけを使う。NaN は含まないこと）
+/// - -0.0 と 0.0 は同じ値として扱う
+/// - 項目は2つ以上必要
+pub fn kendall(x: &[f64], y: &[f64]) -> Kendall {
+    assert_eq!(x.len(), y.len(), "2つの順位づけの長さが異なります");
+    let n = x.len();
+    // 組が1つもないと τ は 0/0 になる
+    assert!(n >= 2, "項目は2つ以上必要です: {}", n);
+    // total_cmp は -0.0 < 0.0 とするが、同値の判定は == なので、比較の前に 0.0 にそろえる
+    let x = &without_negative_zero(x);
+    let y = &without_negative_zero(y);
+
+    // x、同値は y の順に並べる
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| x[i].total_cmp(&x[j]).then(y[i].total_cmp(&y[j])));
+    let x_ties = tie_lengths(&order.iter().map(|&i| x[i]).collect::<Vec<_>>());
+    let joint_ties = tie_lengths(&order.iter().map(|&i| (x[i], y[i])).collect::<Vec<_>>());
+
+    // y を順位に置き換え、x の順に並べた列の転倒数を数える（y の同値は転倒にならない）
+    let mut by_y: Vec<usize> = (0..n).collect();
+    by_y.sort_by(|&i, &j| y[i].total_cmp(&y[j]));
+    let mut y_rank = vec![0; n];
+    let mut rank = 0;
+    for (k, &i) in by_y.iter().enumerate() {
+        if k > 0 && y[by_y[k - 1]].total_cmp(&y[i]) == Ordering::Less {
+            rank += 1;
+        }
+        y_rank[i] = rank;
+    }
+    let y_ties = tie_lengths(&by_y.iter().map(|&i| y[i]).collect::<Vec<_>>());
+    let mut ys: Vec<i32> = order.iter().map(|&i| y_rank[i]).collect();
+    let discordant = count_inversion_with_merge_sort(&mut ys);
+
+    let nf = n as f64;
+    let n0 = nf * (nf - 1.0) / 2.0;
+    let (n1, n2, n3) = (
+        tied_pairs(&x_ties),
+        tied_pairs(&y_ties),
+        tied_pairs(&joint_ties),
+    );
+    // 一致 - 不一致 = (どちらも同順位でない組) - 2 × 不一致
+    let s = n0 - n1 - n2 + n3 - 2.0 * discordant as f64;
+
+    let tau_a = s / n0;
+    let tau_b = (n0 > n1 && n0 > n2).then(|| s / ((n0 - n1) * (n0 - n2)).sqrt());
+
+    // 同順位を補正した S の分散
+    let sum = |ties: &[f64], f: fn(f64) -> f64| ties.iter().map(|&t| f(t)).sum::<f64>();
+    let v0 = nf * (nf - 1.0) * (2.0 * nf + 5.0);
+    let vt = sum(&x_ties, |t| t * (t - 1.0) * (2.0 * t + 5.0));
+    let vu = sum(&y_ties, |u| u * (u - 1.0) * (2.0 * u + 5.0));
+    let mut variance = (v0 - vt - vu) / 18.0;
+    if n > 1 {
+        variance += 2.0 * n1 * n2 / (nf * (nf - 1.0));
+    }
+    if n > 2 {
+        let t3 = sum(&x_ties, |t| t * (t - 1.0) * (t - 2.0));
+        let u3 = sum(&y_ties, |u| u * (u - 1.0) * (u - 2.0));
+        variance += t3 * u3 / (9.0 * nf * (nf - 1.0) * (nf - 2.0));
+    }
+    let (z, p_value) = if variance > 0.0 {
+        let z = s / variance.sqrt();
+        // 近似の誤差で z = 0 でも 1 をわずかに超えるので抑える
+        (z, erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0))
+    } else {
+        (0.0, 1.0)
+    };
+
+    Kendall {
+        n,
+        distance: discordant,
+        tau_a,
+        tau_b,
+        z,
+        p_value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naive;
+    use rand::Rng;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_kendall_permutations() {
+        // 同順位がなければ距離は転倒数で、τ-a = τ-b
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let y = [2.0, 3.0, 8.0, 6.0, 1.0];
+        let k = kendall(&x, &y);
+        assert_eq!(k.distance, naive(&[2, 3, 8, 6, 1]));
+        assert_close(k.tau_a, 0.0);
+        assert_close(k.tau_b.unwrap(), 0.0);
+
+        let k = kendall(&x, &x);
+        assert_eq!(k.distance, 0);
+        assert_close(k.tau_b.unwrap(), 1.0);
+        let reversed = [5.0, 4.0, 3.0, 2.0, 1.0];
+        assert_close(kendall(&x, &reversed).tau_a, -1.0);
+    }
+
+    #[test]
+    fn test_kendall_with_ties() {
+        // scipy.stats.kendalltau([12, 2, 1, 12, 2], [1, 4, 7, 1, 0])
+        // => statistic=-0.47140452079103173, pvalue=0.2827454599327748
+        let x = [12.0, 2.0, 1.0, 12.0, 2.0];
+        let y = [1.0, 4.0, 7.0, 1.0, 0.0];
+        let k = kendall(&x, &y);
+        assert_close(k.tau_b.unwrap(), -0.47140452079103173);
+        assert!((k.p_value - 0.2827454599327748).abs() < 1e-5);
+        assert_close(k.tau_a, -0.4);
+    }
+
+    #[test]
+    fn test_kendall_tau_b_undefined_when_all_tied() {
+        let k = kendall(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0]);
+        assert_eq!(k.tau_b, None);
+        assert_close(k.tau_a, 0.0);
+        assert_eq!(kendall(&[1.0, 2.0], &[5.0, 5.0]).tau_b, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "項目は2つ以上必要です")]
+    fn test_kendall_rejects_single_item() {
+        kendall(&[1.0], &[2.0]);
+    }
+
+    #[test]
+    fn test_kendall_negative_zero() {
+        let k = kendall(&[0.0, -0.0, 1.0], &[1.0, 2.0, 3.0]);
+        assert_eq!(k.distance, 0);
+        assert_close(k.tau_a, 2.0 / 3.0);
+        let k = kendall(&[1.0, 2.0, 3.0], &[0.0, 1.0, -0.0]);
+        assert_eq!(k.distance, 1);
+        assert_close(k.tau_a, 0.0);
+    }
+
+    #[test]
+    fn test_kendall_p_value_at_most_one() {
+        // 転倒数が全組数の半分なので S = 0、z = 0
+        let k = kendall(&[1.0, 2.0, 3.0, 4.0], &[1.0, 4.0, 3.0, 2.0]);
+        assert_close(k.z, 0.0);
+        assert_eq!(k.p_value, 1.0);
+    }
+
+    #[test]
+    fn test_kendall_random_against_pairs() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.gen_range(2..40);
+            let x: Vec<f64> = (0..n).map(|_| rng.gen_range(0..5) as f64).collect();
+            let y: Vec<f64> = (0..n).map(|_| rng.gen_range(0..5) as f64).collect();
+            let (mut concordant, mut discordant) = (0, 0);
+            let (mut x_tied, mut y_tied) = (0, 0);
+            for i in 0..n {
+                for j in i + 1..n {
+                    let s = (x[i] - x[j]) * (y[i] - y[j]);
+                    if s > 0.0 {
+                        concordant += 1;
+                    } else if s < 0.0 {
+                        discordant += 1;
+                    }
+                    x_tied += (x[i] == x[j]) as i64;
+                    y_tied += (y[i] == y[j]) as i64;
+                }
+            }
+            let k = kendall(&x, &y);
+            let pairs = (n * (n - 1) / 2) as f64;
+            assert_eq!(k.distance, discordant);
+            assert_close(k.tau_a, (concordant - discordant) as f64 / pairs);
+            if x_tied as f64 != pairs && y_tied as f64 != pairs {
+                let denominator = ((pairs - x_tied as f64) * (pairs - y_tied as f64)).sqrt();
+                assert_close(
+                    k.tau_b.unwrap(),
+                    (concordant - discordant) as f64 / denominator,
+                );
+            } else {
+                assert_eq!(k.tau_b, None);
+            }
+        }
+    }
+}
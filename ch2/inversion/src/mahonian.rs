@@ -0,0 +1,353 @@
+//! Mahonian 数（転倒数がちょうど k の長さ n の順列の個数）と、その一様サンプリング
+//! - M(m, j) = Σ_{c=0}^{m-1} M(m-1, j-c)（先頭の Lehmer code の桁 c で場合分け）
+//! - 窓の和は M(m, j) = (M(m, j-1) - M(m-1, j-m)) + M(m-1, j) で1つずつずらす
+//! - M(m, ·) は j = m(m-1)/4 を中心に左右対称で、そこまでは単調に増える
+
+use crate::permutation::from_lehmer_code;
+use rand::Rng;
+
+/// 長さ m の順列の転倒数の最大値 m(m-1)/2
+fn max_inversions(m: usize) -> usize {
+    m * m.saturating_sub(1) / 2
+}
+
+/// M(n, k) を正確に求める。u128 に収まらなければ None
+pub fn mahonian(n: usize, k: usize) -> Option<u128> {
+    let max = max_inversions(n);
+    if k > max {
+        return Some(0);
+    }
+    // 対称性で k を前半に寄せると、途中の値はすべて M(n, k) 以下になる
+    let k = k.min(max - k);
+    let mut row = vec![0u128; k + 1];
+    row[0] = 1;
+    for m in 1..=n {
+        let prev = row;
+        row = vec![0; k + 1];
+        for j in 0..=k {
+            // 窓 [j-m+1, j] の和（M(m, j-1) は窓 [j-m, j-1] の和）
+            let slide = if j == 0 { 0 } else { row[j - 1] };
+            let leaving = if j >= m { prev[j - m] } else { 0 };
+            row[j] = (slide - leaving).checked_add(prev[j])?;
+        }
+    }
+    Some(row[k])
+}
+
+/// M(n, k) mod p
+/// - 途中の和は u128 で取るので、p は u64 の全範囲でよい
+pub fn mahonian_mod(n: usize, k: usize, p: u64) -> u64 {
+    assert!(p > 0, "法は正の数にしてください");
+    if k > max_inversions(n) {
+        return 0;
+    }
+    let p = p as u128;
+    let mut row = vec![0u128; k + 1];
+    row[0] = 1 % p;
+    for m in 1..=n {
+        let prev = row;
+        row = vec![0; k + 1];
+        for j in 0..=k {
+            let slide = if j == 0 { 0 } else { row[j - 1] };
+            let leaving = if j >= m { prev[j - m] } else { 0 };
+            row[j] = ((slide + p - leaving) % p + prev[j]) % p;
+        }
+    }
+    row[k] as u64
+}
+
+/// ln M(m, j) の表（j ≤ k）
+/// - 値が大きすぎて整数では持てないので、対数で持つ
+/// - 各行は単調に増える前半だけを求め、後半は対称性で読む（引き算の桁落ちを避ける）
+/// - 全行を持つとメモリが O(n·k) になるので、約 √n 行ごとの行だけを残し、
+///   ほかの行は読むときに直前の残した行から計算し直す（メモリ O(k√n)）
+struct LogMahonian {
+    n: usize,
+    k: usize,
+    /// 残す行の間隔
+    stride: usize,
+    /// checkpoints[i] = 行 i·stride
+    checkpoints: Vec<Vec<f64>>,
+    /// 最後に計算し直した区間の行（行 cache_start から）
+    cache_start: usize,
+    cache: Vec<Vec<f64>>,
+}
+
+impl LogMahonian {
+    fn new(n: usize, k: usize) -> Self {
+        let stride = ((n as f64).sqrt() as usize).max(1);
+        let mut checkpoints = Vec::with_capacity(n / stride + 1);
+        let mut row = vec![0.0];
+        for m in 0..=n {
+            if m > 0 {
+                row = Self::next_row(&row, m, k);
+            }
+            if m.is_multiple_of(stride) {
+                checkpoints.push(row.clone());
+            }
+        }
+        Self {
+            n,
+            k,
+            stride,
+            checkpoints,
+            cache_start: 0,
+            cache: Vec::new(),
+        }
+    }
+
+    /// 行 m - 1 から行 m を求める
+    fn next_row(prev: &[f64], m: usize, k: usize) -> Vec<f64> {
+        let len = k.min(max_inversions(m) / 2) + 1;
+        let mut row: Vec<f64> = Vec::with_capacity(len);
+        for j in 0..len {
+            let slide = if j == 0 {
+                f64::NEG_INFINITY
+            } else {
+                row[j - 1]
+            };
+            let entering = Self::lookup(prev, m - 1, j);
+            let leaving = if j >= m {
+                Self::lookup(prev, m - 1, j - m)
+            } else {
+                f64::NEG_INFINITY
+            };
+            // ln(e^slide - e^leaving + e^entering)。前半では leaving ≤ slide
+            let top = slide.max(entering);
+            let sum = (slide - top).exp() - (leaving - top).exp() + (entering - top).exp();
+            row.push(top + sum.ln());
+        }
+        row
+    }
+
+    fn lookup(row: &[f64], m: usize, j: usize) -> f64 {
+        let max = max_inversions(m);
+        if j > max {
+            return f64::NEG_INFINITY;
+        }
+        row[j.min(max - j)]
+    }
+
+    /// 行 m（m の降順に読むと、区間ごとに1回だけ計算し直す）
+    fn row(&mut self, m: usize) -> &[f64] {
+        assert!(m <= self.n);
+        if !(self.cache_start..self.cache_start + self.cache.len()).contains(&m) {
+            let start = m / self.stride * self.stride;
+            let end = (start + self.stride).min(self.n + 1);
+            let mut rows = Vec::with_capacity(end - start);
+            rows.push(self.checkpoints[m / self.stride].clone());
+            for r in start + 1..end {
+                let next = Self::next_row(rows.last().unwrap(), r, self.k);
+                rows.push(next);
+            }
+            self.cache_start = start;
+            self.cache = rows;
+        }
+        &self.cache[m - self.cache_start]
+    }
+
+    /// ln M(m, j)
+    fn get(&mut self, m: usize, j: usize) -> f64 {
+        Self::lookup(self.row(m), m, j)
+    }
+}
+
+/// 長さ size の集合から count 個を一様に選ぶ（Floyd の方法、昇順で返す）
+fn random_subset(rng: &mut impl Rng, size: usize, count: usize) -> Vec<usize> {
+    let mut chosen = std::collections::BTreeSet::new();
+    for j in size - count..size {
+        let t = rng.gen_range(0..=j);
+        if !chosen.insert(t) {
+            chosen.insert(j);
+        }
+    }
+    chosen.into_iter().collect()
+}
+
+/// 転倒数がちょうど k の長さ n の順列を一様に選ぶ（k が大きすぎれば None）
+/// - Lehmer code の桁は、残り m 個の位置の桁が [0, m-1] の範囲で、和が k になるものを一様に選べばよい
+/// - m > k + 1 の桁は上限にかからないので、それらの和 s は「非負整数の和が s になる並べ方」
+///   C(s + N - 1, N - 1) 通りとして閉じた式で数え、星と棒で一様に分ける
+/// - 残りの K = min(n, k + 1) 桁は、先頭から残りの並べ方の個数に比例する確率で選ぶ
+/// - 時間 O(n + k·K)、メモリ O(n + k√K)。n が大きくても k が小さければ速い
+pub fn random_permutation_with_inversions(n: usize, k: usize) -> Option<Vec<usize>> {
+    if k > max_inversions(n) {
+        return None;
+    }
+    let mut rng = rand::thread_rng();
+    let constrained = n.min(k + 1);
+    let free = n - constrained;
+    let mut table = LogMahonian::new(constrained, k);
+
+    // 上限にかからない桁の和 s を、ln C(s + N - 1, N - 1) + ln M(K, k - s) に比例して選ぶ
+    let mut rest = k;
+    let mut code = vec![0; n];
+    if free > 0 {
+        let mut log_weights = Vec::with_capacity(k + 1);
+        let mut log_ways = 0.0;
+        for s in 0..=k {
+            if s > 0 {
+                log_ways += ((free - 1 + s) as f64 / s as f64).ln();
+            }
+            log_weights.push(log_ways + table.get(constrained, k - s));
+        }
+        let top = log_weights
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let total: f64 = log_weights.iter().map(|w| (w - top).exp()).sum();
+        let mut u = rng.gen::<f64>() * total;
+        let mut sum = 0;
+        for (s, w) in log_weights.iter().enumerate() {
+            let weight = (w - top).exp();
+            if weight > 0.0 {
+                sum = s;
+                if u < weight {
+                    break;
+                }
+                u -= weight;
+            }
+        }
+        // s 個の星と N - 1 本の棒の並びを一様に選び、棒の間の星の数を桁にする
+        for (index, star) in random_subset(&mut rng, sum + free - 1, sum)
+            .into_iter()
+            .enumerate()
+        {
+            code[star - index] += 1;
+        }
+        rest -= sum;
+    }
+
+    for (i, digit) in code.iter_mut().enumerate().skip(free) {
+        // 残り m 個の先頭の桁 c ∈ [0, m-1]、あとの m-1 個で rest - c 個の転倒を作る
+        let m = n - i;
+        let total = table.get(m, rest);
+        let lowest = rest.saturating_sub(max_inversions(m - 1));
+        let highest = rest.min(m - 1);
+        let below = table.row(m - 1);
+        let mut u: f64 = rng.gen();
+        let mut chosen = highest;
+        for c in lowest..=highest {
+            let weight = (LogMahonian::lookup(below, m - 1, rest - c) - total).exp();
+            if u < weight {
+                chosen = c;
+                break;
+            }
+            u -= weight;
+        }
+        *digit = chosen;
+        rest -= chosen;
+    }
+    from_lehmer_code(&code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_inversion_with_merge_sort;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_mahonian_small() {
+        // n = 4: 1, 3, 5, 6, 5, 3, 1
+        let row: Vec<u128> = (0..8).map(|k| mahonian(4, k).unwrap()).collect();
+        assert_eq!(row, vec![1, 3, 5, 6, 5, 3, 1, 0]);
+        assert_eq!(mahonian(0, 0), Some(1));
+        // 行の和は n!
+        let sum: u128 = (0..=45).map(|k| mahonian(10, k).unwrap()).sum();
+        assert_eq!(sum, 3_628_800);
+    }
+
+    #[test]
+    fn test_mahonian_overflow_and_mod() {
+        assert!(mahonian(34, 280).is_some());
+        assert_eq!(mahonian(60, 885), None);
+        let p = 1_000_000_007;
+        for (n, k) in [(10, 20), (20, 95), (34, 280)] {
+            let exact = mahonian(n, k).unwrap();
+            assert_eq!(mahonian_mod(n, k, p) as u128, exact % p as u128);
+        }
+        // 法が 2^63 を超えてもあふれない
+        let p = u64::MAX - 58;
+        assert_eq!(
+            mahonian_mod(34, 280, p) as u128,
+            mahonian(34, 280).unwrap() % p as u128
+        );
+        assert_eq!(mahonian_mod(4, 7, p), 0);
+        assert_eq!(mahonian_mod(4, usize::MAX, 7), 0);
+    }
+
+    #[test]
+    fn test_log_table_rows_in_any_order() {
+        // 残した行の間の行も、どの順に読んでも同じ値になる
+        let mut table = LogMahonian::new(40, 100);
+        let descending: Vec<Vec<f64>> = (0..=40).rev().map(|m| table.row(m).to_vec()).collect();
+        for (i, m) in (0..=40).rev().enumerate() {
+            assert_eq!(table.row(m), &descending[i][..]);
+        }
+    }
+
+    #[test]
+    fn test_log_table_matches_exact() {
+        let mut table = LogMahonian::new(30, 435);
+        for m in [1, 5, 17, 30] {
+            for j in 0..=max_inversions(m) {
+                let exact = mahonian(m, j).unwrap() as f64;
+                let approx = table.get(m, j).exp();
+                assert!((approx / exact - 1.0).abs() < 1e-9, "M({}, {})", m, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_permutation_has_k_inversions() {
+        for (n, k) in [(0, 0), (1, 0), (10, 0), (10, 45), (50, 300), (200, 5000)] {
+            let perm = random_permutation_with_inversions(n, k).unwrap();
+            let mut a: Vec<i32> = perm.iter().map(|&v| v as i32).collect();
+            assert_eq!(count_inversion_with_merge_sort(&mut a), k as i64);
+        }
+        assert_eq!(random_permutation_with_inversions(4, 7), None);
+    }
+
+    #[test]
+    fn test_random_permutation_large_n_small_k() {
+        // 表は min(n, k + 1) 行ぶんしか作らないので、n が大きくても使える
+        let n = 1 << 20;
+        let perm = random_permutation_with_inversions(n, 1000).unwrap();
+        let mut a: Vec<i32> = perm.iter().map(|&v| v as i32).collect();
+        assert_eq!(count_inversion_with_merge_sort(&mut a), 1000);
+    }
+
+    #[test]
+    fn test_random_permutation_is_uniform() {
+        // 長さ 4・転倒数 3 の順列は 6 通り
+        let trials = 12_000;
+        let mut counts: HashMap<Vec<usize>, usize> = HashMap::new();
+        for _ in 0..trials {
+            *counts
+                .entry(random_permutation_with_inversions(4, 3).unwrap())
+                .or_default() += 1;
+        }
+        assert_eq!(counts.len(), 6);
+        for &count in counts.values() {
+            // 期待値 2000、標準偏差はおよそ 41
+            assert!((1750..2250).contains(&count), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_random_permutation_is_uniform_with_free_digits() {
+        // 長さ 5・転倒数 2 の順列は 9 通り。先頭 2 桁は上限にかからない桁として選ぶ
+        let trials = 18_000;
+        let mut counts: HashMap<Vec<usize>, usize> = HashMap::new();
+        for _ in 0..trials {
+            *counts
+                .entry(random_permutation_with_inversions(5, 2).unwrap())
+                .or_default() += 1;
+        }
+        assert_eq!(counts.len(), 9);
+        for &count in counts.values() {
+            assert!((1750..2250).contains(&count), "{:?}", counts);
+        }
+    }
+}
@@ -1,109 +1,83 @@
-fn naive(a: &[i32]) -> i64 {
-    let mut count = 0;
-    for i in 0..a.len() {
-        for j in i + 1..a.len() {
-            if a[i] > a[j] {
-                count += 1;
+use inversion::{count_inversion_with_merge_sort, kendall, naive, scanner};
+
+/// 2列の順位（1行に1項目、空白区切り）を読み、Kendall の τ を表示する
+fn kendall_mode() {
+    let mut x = Vec::new();
+    let mut y = Vec::new();
+    for (line_no, line) in std::io::stdin().lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("{} 行目: 入力を読めません: {}", line_no + 1, e);
+                std::process::exit(2);
             }
+        };
+        if line.trim().is_empty() {
+            continue;
         }
+        let columns = match line
+            .split_whitespace()
+            .map(|s| s.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(columns) if columns.len() == 2 && columns.iter().all(|v| !v.is_nan()) => columns,
+            _ => {
+                eprintln!("{} 行目: 数値を2つ指定してください: {}", line_no + 1, line);
+                std::process::exit(2);
+            }
+        };
+        x.push(columns[0]);
+        y.push(columns[1]);
     }
-    count
-}
 
-/// マージソートを利用して転倒数を求める    
-fn count_inversion_with_merge_sort(arr: &mut [i32]) -> i64 {
-    if arr.len() <= 1 {
-        return 0;
+    if x.len() < 2 {
+        eprintln!("項目を2つ以上指定してください（{} 個でした）", x.len());
+        eprintln!("使い方: inversion kendall < 1行に2列の順位");
+        std::process::exit(2);
     }
 
-    // 結果配列を初期化
-    let mut result = vec![0; arr.len()];
-    let mut result_index = 0;
-
-    let mut count = 0;
-
-    let mid = arr.len() / 2;
-    count += count_inversion_with_merge_sort(&mut arr[..mid]);
-    count += count_inversion_with_merge_sort(&mut arr[mid..]);
-
-    let mut left_index = 0;
-    let mut right_index = mid;
-
-    while left_index < mid && right_index < arr.len() {
-        // 安定ソートのため、同じ場合は左を採用
-        if arr[left_index] <= arr[right_index] {
-            result[result_index] = arr[left_index];
-            left_index += 1;
-            // 左側の要素の方が小さい場合は転倒数にはならない
-        } else {
-            result[result_index] = arr[right_index];
-            right_index += 1;
-            // 左側の残りの要素数分がarr[right_index]より大きい（転倒数）
-            count += (mid - left_index) as i64;
-        }
-        result_index += 1;
+    let k = kendall::kendall(&x, &y);
+    println!("項目数: {}", k.n);
+    println!("Kendall の τ 距離: {}", k.distance);
+    println!("τ-a: {:.6}", k.tau_a);
+    match k.tau_b {
+        Some(tau_b) => println!("τ-b: {:.6}", tau_b),
+        None => println!("τ-b: 定義されません（一方の列がすべて同順位です）"),
     }
-    // 残りの要素は転倒数にならないのでOK
-    // 右が先に空になり、左だけが残っている場合
-    while left_index < mid {
-        result[result_index] = arr[left_index];
-        left_index += 1;
-        result_index += 1;
-    }
-
-    // 左が先に空になり、右だけが残っている場合
-    while right_index < arr.len() {
-        result[result_index] = arr[right_index];
-        right_index += 1;
-        result_index += 1;
-    }
-
-    // 結果を元の配列にコピー
-    arr.copy_from_slice(&result);
-
-    count
+    println!("z: {:.4}, p 値（両側）: {:.4e}", k.z, k.p_value);
 }
 
 fn main() {
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
-    let n: usize = input.trim().parse().unwrap();
-
-    input.clear();
-    std::io::stdin().read_line(&mut input).unwrap();
-    let mut a: Vec<i32> = input
-        .split_whitespace()
-        .map(|x| x.parse().unwrap())
-        .collect();
-
-    println!("{}", count_inversion_with_merge_sort(&mut a));
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_naive_1() {
-        let a = vec![1, 2, 3, 4, 5];
-        assert_eq!(naive(&a), 0);
-    }
-
-    #[test]
-    fn test_naive_2() {
-        let a = vec![2, 3, 8, 6, 1];
-        assert_eq!(naive(&a), 5);
-    }
-
-    #[test]
-    fn test_count_inversion_with_merge_sort_1() {
-        let mut a = vec![1, 2, 3, 4, 5];
-        assert_eq!(count_inversion_with_merge_sort(&mut a), 0);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut check_naive = false;
+    match args.first().map(String::as_str) {
+        Some("kendall") => {
+            kendall_mode();
+            return;
+        }
+        Some("--naive") if args.len() == 1 => check_naive = true,
+        None => {}
+        Some(_) => {
+            eprintln!("使い方: inversion [--naive] | inversion kendall");
+            std::process::exit(2);
+        }
     }
 
-    #[test]
-    fn test_count_inversion_with_mer_2() {
-        let mut a = vec![2, 3, 8, 6, 1];
-        assert_eq!(count_inversion_with_merge_sort(&mut a), 5);
+    let mut a = match scanner::read_sequence(std::io::stdin().lock()) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let expected = check_naive.then(|| naive(&a));
+    let count = count_inversion_with_merge_sort(&mut a);
+    println!("{}", count);
+    if let Some(expected) = expected {
+        if expected != count {
+            eprintln!("素朴な方法の結果 {} と一致しません", expected);
+            std::process::exit(1);
+        }
     }
 }
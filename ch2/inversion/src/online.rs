@@ -0,0 +1,313 @@
+//! 要素を1つずつ受け取りながら転倒数を数える（オンライン）
+//! - 新しい要素 x は、それまでの要素のうち x より大きいものすべてと転倒する
+//! - 値の範囲がわからないときは順序統計木（部分木の大きさを持つ AVL 木）、
+//!   とりうる値が先にわかっているときは座標圧縮したフェニック木で数える
+//! - どちらも1要素 O(log n)
+
+use crate::fenwick::Fenwick;
+use std::cmp::Ordering;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    key: T,
+    /// 同じ値の個数
+    count: usize,
+    /// 部分木の要素数（重複も数える）
+    size: usize,
+    height: u32,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn height<T>(link: &Link<T>) -> u32 {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+impl<T> Node<T> {
+    fn update(&mut self) {
+        self.size = size(&self.left) + self.count + size(&self.right);
+        self.height = height(&self.left).max(height(&self.right)) + 1;
+    }
+
+    fn balance_factor(&self) -> i64 {
+        height(&self.left) as i64 - height(&self.right) as i64
+    }
+}
+
+fn rotate_right<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut left = node.left.take().expect("左の子がある");
+    node.left = left.right.take();
+    node.update();
+    left.right = Some(node);
+    left.update();
+    left
+}
+
+fn rotate_left<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut right = node.right.take().expect("右の子がある");
+    node.right = right.left.take();
+    node.update();
+    right.left = Some(node);
+    right.update();
+    right
+}
+
+/// 左右の高さの差が 2 になった節点を回転で直す
+fn rebalance<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    node.update();
+    let balance = node.balance_factor();
+    if balance > 1 {
+        if node.left.as_ref().unwrap().balance_factor() < 0 {
+            node.left = Some(rotate_left(node.left.take().unwrap()));
+        }
+        return rotate_right(node);
+    }
+    if balance < -1 {
+        if node.right.as_ref().unwrap().balance_factor() > 0 {
+            node.right = Some(rotate_right(node.right.take().unwrap()));
+        }
+        return rotate_left(node);
+    }
+    node
+}
+
+fn insert<T: Ord>(link: Link<T>, key: T) -> Box<Node<T>> {
+    let Some(mut node) = link else {
+        return Box::new(Node {
+            key,
+            count: 1,
+            size: 1,
+            height: 1,
+            left: None,
+            right: None,
+        });
+    };
+    match key.cmp(&node.key) {
+        Ordering::Less => node.left = Some(insert(node.left.take(), key)),
+        Ordering::Greater => node.right = Some(insert(node.right.take(), key)),
+        Ordering::Equal => node.count += 1,
+    }
+    rebalance(node)
+}
+
+/// 順序統計木（重複を許す）
+pub struct OrderStatisticTree<T> {
+    root: Link<T>,
+}
+
+impl<T: Ord> OrderStatisticTree<T> {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn insert(&mut self, key: T) {
+        self.root = Some(insert(self.root.take(), key));
+    }
+
+    /// x より小さい要素の個数
+    pub fn count_less(&self, x: &T) -> usize {
+        let mut count = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match x.cmp(&node.key) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return count + size(&node.left),
+                Ordering::Greater => {
+                    count += size(&node.left) + node.count;
+                    link = &node.right;
+                }
+            }
+        }
+        count
+    }
+
+    /// x より大きい要素の個数
+    pub fn count_greater(&self, x: &T) -> usize {
+        let mut count = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match x.cmp(&node.key) {
+                Ordering::Greater => link = &node.right,
+                Ordering::Equal => return count + size(&node.right),
+                Ordering::Less => {
+                    count += size(&node.right) + node.count;
+                    link = &node.left;
+                }
+            }
+        }
+        count
+    }
+
+    /// k 番目（0 始まり）に小さい要素
+    pub fn select(&self, k: usize) -> Option<&T> {
+        let mut k = k;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left = size(&node.left);
+            if k < left {
+                link = &node.left;
+            } else if k < left + node.count {
+                return Some(&node.key);
+            } else {
+                k -= left + node.count;
+                link = &node.right;
+            }
+        }
+        None
+    }
+
+    /// 木の高さ（平衡しているかをテストで確かめる）
+    #[cfg(test)]
+    fn height(&self) -> u32 {
+        height(&self.root)
+    }
+}
+
+impl<T: Ord> Default for OrderStatisticTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 値の範囲を問わないオンラインの転倒数
+pub struct OnlineInversions<T> {
+    tree: OrderStatisticTree<T>,
+    count: i64,
+}
+
+impl<T: Ord> OnlineInversions<T> {
+    pub fn new() -> Self {
+        Self {
+            tree: OrderStatisticTree::new(),
+            count: 0,
+        }
+    }
+
+    /// x を末尾に加え、それまでの転倒数を返す
+    pub fn push(&mut self, x: T) -> i64 {
+        self.count += self.tree.count_greater(&x) as i64;
+        self.tree.insert(x);
+        self.count
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+}
+
+impl<T: Ord> Default for OnlineInversions<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// とりうる値があらかじめわかっている場合のオンラインの転倒数
+pub struct OnlineInversionsFenwick<T> {
+    universe: Vec<T>,
+    fenwick: Fenwick,
+    seen: i64,
+    count: i64,
+}
+
+impl<T: Ord> OnlineInversionsFenwick<T> {
+    /// universe はとりうる値（順不同・重複可）
+    pub fn new(universe: Vec<T>) -> Self {
+        let mut universe = universe;
+        universe.sort();
+        universe.dedup();
+        let fenwick = Fenwick::new(universe.len());
+        Self {
+            universe,
+            fenwick,
+            seen: 0,
+            count: 0,
+        }
+    }
+
+    /// x を末尾に加え、それまでの転倒数を返す。x がとりうる値になければ None
+    pub fn push(&mut self, x: T) -> Option<i64> {
+        let r = self.universe.binary_search(&x).ok()?;
+        self.count += self.seen - self.fenwick.prefix_sum(r + 1);
+        self.fenwick.add(r, 1);
+        self.seen += 1;
+        Some(self.count)
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_inversion_with_merge_sort;
+    use rand::Rng;
+
+    #[test]
+    fn test_order_statistic_tree() {
+        let mut tree = OrderStatisticTree::new();
+        assert!(tree.is_empty());
+        for x in [5, 1, 5, 9, 3, 5] {
+            tree.insert(x);
+        }
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.count_less(&5), 2);
+        assert_eq!(tree.count_greater(&5), 1);
+        assert_eq!(tree.count_greater(&4), 4);
+        assert_eq!(tree.count_less(&100), 6);
+        let sorted: Vec<i32> = (0..6).map(|k| *tree.select(k).unwrap()).collect();
+        assert_eq!(sorted, vec![1, 3, 5, 5, 5, 9]);
+        assert_eq!(tree.select(6), None);
+    }
+
+    #[test]
+    fn test_tree_stays_balanced() {
+        // 昇順に入れても高さは 1.44 log2 n 程度に収まる
+        let mut tree = OrderStatisticTree::new();
+        for x in 0..100_000 {
+            tree.insert(x);
+        }
+        assert!(tree.height() <= 25, "height = {}", tree.height());
+    }
+
+    #[test]
+    fn test_online_inversions_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let n = rng.gen_range(0..200);
+            let max = rng.gen_range(1..50);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..max)).collect();
+
+            let mut online = OnlineInversions::new();
+            let mut with_fenwick = OnlineInversionsFenwick::new((0..max).collect());
+            for (i, &x) in a.iter().enumerate() {
+                let expected = count_inversion_with_merge_sort(&mut a[..=i].to_vec());
+                assert_eq!(online.push(x), expected);
+                assert_eq!(with_fenwick.push(x), Some(expected));
+            }
+            assert_eq!(online.count(), with_fenwick.count());
+        }
+    }
+
+    #[test]
+    fn test_fenwick_rejects_unknown_value() {
+        let mut online = OnlineInversionsFenwick::new(vec![10, 20, 30]);
+        assert_eq!(online.push(30), Some(0));
+        assert_eq!(online.push(25), None);
+        assert_eq!(online.push(10), Some(1));
+    }
+}
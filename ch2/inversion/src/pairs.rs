@@ -0,0 +1,153 @@
+//! 転倒している組 (i, j) の列挙
+//! - ボトムアップのマージソートを少しずつ進め、右側の要素を採用するたびに
+//!   左側に残っている要素との組を1つずつ返す
+//! - マージ全体で O(n log n)、組1つにつき O(1) なので、転倒数を k として O(n log n + k)
+//! - イテレータなので、途中でやめればそれ以降のマージは行わない
+
+/// 転倒している組を返すイテレータ
+/// - 組は (i, j)（i < j かつ a[i] > a[j]、添字は元の配列のもの）で、順序はマージの順
+pub struct InversionPairs<'a, T> {
+    a: &'a [T],
+    /// 幅 width の区間ごとに値の昇順に並んだ添字
+    cur: Vec<usize>,
+    /// 幅 2*width の区間ごとにマージしている途中の添字
+    next: Vec<usize>,
+    width: usize,
+    /// マージ中の区間の先頭
+    block: usize,
+    left: usize,
+    right: usize,
+    out: usize,
+    /// 返している途中の組: cur[t..end] の各要素と、元の位置 r の要素
+    pending: Option<(usize, usize, usize)>,
+}
+
+impl<'a, T: Ord> InversionPairs<'a, T> {
+    fn start_block(&mut self, block: usize) {
+        self.block = block;
+        self.left = block;
+        self.right = (block + self.width).min(self.a.len());
+        self.out = block;
+    }
+}
+
+impl<'a, T: Ord> Iterator for InversionPairs<'a, T> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let n = self.a.len();
+        loop {
+            if let Some((t, end, r)) = self.pending {
+                if t < end {
+                    self.pending = Some((t + 1, end, r));
+                    return Some((self.cur[t], r));
+                }
+                self.pending = None;
+            }
+            if self.width >= n {
+                return None;
+            }
+
+            let mid = (self.block + self.width).min(n);
+            let end = (self.block + 2 * self.width).min(n);
+            if self.left < mid && self.right < end {
+                // 安定ソートのため、同じ場合は左を採用（転倒にならない）
+                if self.a[self.cur[self.left]] <= self.a[self.cur[self.right]] {
+                    self.next[self.out] = self.cur[self.left];
+                    self.left += 1;
+                } else {
+                    // 左側の残りの要素はすべて右の要素より大きい（転倒）
+                    let r = self.cur[self.right];
+                    self.next[self.out] = r;
+                    self.right += 1;
+                    self.pending = Some((self.left, mid, r));
+                }
+                self.out += 1;
+                continue;
+            }
+
+            // 残りの要素は転倒にならないのでそのまま移す
+            let rest = (mid - self.left) + (end - self.right);
+            self.next[self.out..self.out + mid - self.left]
+                .copy_from_slice(&self.cur[self.left..mid]);
+            self.next[self.out + mid - self.left..self.out + rest]
+                .copy_from_slice(&self.cur[self.right..end]);
+
+            // 次の区間へ。全区間が終わったら幅を倍にする
+            if end < n {
+                self.start_block(end);
+            } else {
+                std::mem::swap(&mut self.cur, &mut self.next);
+                self.width *= 2;
+                self.start_block(0);
+            }
+        }
+    }
+}
+
+/// 転倒している組をすべて列挙するイテレータを作る
+pub fn inversion_pairs<T: Ord>(a: &[T]) -> InversionPairs<'_, T> {
+    let n = a.len();
+    let mut pairs = InversionPairs {
+        a,
+        cur: (0..n).collect(),
+        next: vec![0; n],
+        width: 1,
+        block: 0,
+        left: 0,
+        right: 0,
+        out: 0,
+        pending: None,
+    };
+    pairs.start_block(0);
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naive;
+    use rand::Rng;
+
+    fn naive_pairs(a: &[i32]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for i in 0..a.len() {
+            for j in i + 1..a.len() {
+                if a[i] > a[j] {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn test_inversion_pairs_1() {
+        let a = vec![2, 3, 8, 6, 1];
+        let mut pairs: Vec<_> = inversion_pairs(&a).collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 4), (1, 4), (2, 3), (2, 4), (3, 4)]);
+    }
+
+    #[test]
+    fn test_inversion_pairs_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300 {
+            let n = rng.gen_range(0..50);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..8)).collect();
+            let mut pairs: Vec<_> = inversion_pairs(&a).collect();
+            assert_eq!(pairs.len() as i64, naive(&a));
+            pairs.sort();
+            assert_eq!(pairs, naive_pairs(&a));
+        }
+    }
+
+    #[test]
+    fn test_inversion_pairs_stops_early() {
+        // 逆順なら約 5×10^11 組あるが、最初の数個だけなら一瞬で終わる
+        let a: Vec<i32> = (0..1_000_000).rev().collect();
+        let first: Vec<_> = inversion_pairs(&a).take(3).collect();
+        assert_eq!(first.len(), 3);
+        assert!(first.iter().all(|&(i, j)| i < j && a[i] > a[j]));
+    }
+}
@@ -0,0 +1,222 @@
+//! 順列と Lehmer code・転倒表・辞書順の順位の相互変換
+//! - 順列は 0..n の並べ替え
+//! - Lehmer code: code[i] = i より右にある perm[i] より小さい値の個数
+//! - 転倒表: table[v] = 値 v より左にある v より大きい値の個数
+//! - 辞書順の順位は Lehmer code を階乗進法で読んだ値（u128 に収まる範囲）
+
+use crate::fenwick::Fenwick;
+use crate::vectors::inversion_vectors;
+
+/// `perm` が 0..n の並べ替えか（出てきた値の表で重複と範囲外を調べる）
+fn is_permutation(perm: &[usize]) -> bool {
+    let mut seen = vec![false; perm.len()];
+    perm.iter()
+        .all(|&v| v < perm.len() && !std::mem::replace(&mut seen[v], true))
+}
+
+/// 順列の Lehmer code。0..n の並べ替えでなければ None
+pub fn lehmer_code(perm: &[usize]) -> Option<Vec<usize>> {
+    if !is_permutation(perm) {
+        return None;
+    }
+    Some(inversion_vectors(perm).smaller_right)
+}
+
+/// 順列の転倒表。0..n の並べ替えでなければ None
+pub fn inversion_table(perm: &[usize]) -> Option<Vec<usize>> {
+    if !is_permutation(perm) {
+        return None;
+    }
+    let greater_left = inversion_vectors(perm).greater_left;
+    let mut table = vec![0; perm.len()];
+    for (i, &v) in perm.iter().enumerate() {
+        table[v] = greater_left[i];
+    }
+    Some(table)
+}
+
+/// 0..n を1で埋めたフェニック木（まだ使っていない値・位置の集合）
+fn full_set(n: usize) -> Fenwick {
+    let mut fenwick = Fenwick::new(n);
+    for i in 0..n {
+        fenwick.add(i, 1);
+    }
+    fenwick
+}
+
+/// Lehmer code から順列を復元する（O(n log n)）
+/// - code[i] ≥ n - i なら None
+pub fn from_lehmer_code(code: &[usize]) -> Option<Vec<usize>> {
+    let n = code.len();
+    let mut unused = full_set(n);
+    let mut perm = Vec::with_capacity(n);
+    for (i, &c) in code.iter().enumerate() {
+        if c >= n - i {
+            return None;
+        }
+        // 残っている値のうち c 番目に小さいもの
+        let v = unused.kth(c as i64);
+        unused.add(v, -1);
+        perm.push(v);
+    }
+    Some(perm)
+}
+
+/// 転倒表から順列を復元する（O(n log n)）
+/// - table[v] ≥ n - v なら None
+pub fn from_inversion_table(table: &[usize]) -> Option<Vec<usize>> {
+    let n = table.len();
+    let mut free = full_set(n);
+    let mut perm = vec![0; n];
+    // 小さい値から置く。空いている位置はあとで大きい値が埋めるので、
+    // v の左に空きを table[v] 個残せばよい
+    for (v, &t) in table.iter().enumerate() {
+        if t >= n - v {
+            return None;
+        }
+        let pos = free.kth(t as i64);
+        free.add(pos, -1);
+        perm[pos] = v;
+    }
+    Some(perm)
+}
+
+/// 辞書順の順位（0 始まり）。0..n の並べ替えでないか、u128 に収まらなければ None
+pub fn rank(perm: &[usize]) -> Option<u128> {
+    let n = perm.len();
+    // 階乗進法: ((code[0]·(n-1) + code[1])·(n-2) + ...)
+    lehmer_code(perm)?
+        .iter()
+        .enumerate()
+        .try_fold(0u128, |r, (i, &c)| {
+            r.checked_mul((n - i) as u128)?.checked_add(c as u128)
+        })
+}
+
+/// 長さ n の順列で辞書順の順位が r のもの。r ≥ n! なら None
+pub fn unrank(r: u128, n: usize) -> Option<Vec<usize>> {
+    // 下の桁から、i 桁目を i で割った余りとして取り出す
+    let mut code = vec![0; n];
+    let mut rest = r;
+    for i in 1..=n {
+        code[n - i] = (rest % i as u128) as usize;
+        rest /= i as u128;
+    }
+    if rest != 0 {
+        return None;
+    }
+    from_lehmer_code(&code)
+}
+
+/// 辞書順で次の並びにする。最後の並びなら最初の並びに戻して false を返す
+pub fn next_permutation<T: Ord>(a: &mut [T]) -> bool {
+    // 右から見て初めて昇順になっている位置 i を探す
+    let Some(i) = (1..a.len()).rev().find(|&i| a[i - 1] < a[i]) else {
+        a.reverse();
+        return false;
+    };
+    // a[i-1] より大きい最も右の要素と入れ替え、右側を昇順にする
+    let j = (i..a.len()).rev().find(|&j| a[j] > a[i - 1]).unwrap();
+    a.swap(i - 1, j);
+    a[i..].reverse();
+    true
+}
+
+/// 辞書順で前の並びにする。最初の並びなら最後の並びに戻して false を返す
+pub fn prev_permutation<T: Ord>(a: &mut [T]) -> bool {
+    let Some(i) = (1..a.len()).rev().find(|&i| a[i - 1] > a[i]) else {
+        a.reverse();
+        return false;
+    };
+    let j = (i..a.len()).rev().find(|&j| a[j] < a[i - 1]).unwrap();
+    a.swap(i - 1, j);
+    a[i..].reverse();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+
+    #[test]
+    fn test_lehmer_code_and_inversion_table() {
+        let perm = [2, 0, 3, 1];
+        assert_eq!(lehmer_code(&perm), Some(vec![2, 0, 1, 0]));
+        assert_eq!(inversion_table(&perm), Some(vec![1, 2, 0, 0]));
+        assert_eq!(from_lehmer_code(&[2, 0, 1, 0]), Some(perm.to_vec()));
+        assert_eq!(from_inversion_table(&[1, 2, 0, 0]), Some(perm.to_vec()));
+        assert_eq!(from_lehmer_code(&[0, 3, 0, 0]), None);
+        assert_eq!(from_inversion_table(&[0, 0, 2, 0]), None);
+    }
+
+    #[test]
+    fn test_rank_enumerates_in_lexicographic_order() {
+        // next_permutation で辿った順番が順位と一致する
+        let mut perm: Vec<usize> = (0..5).collect();
+        let mut r = 0;
+        loop {
+            assert_eq!(rank(&perm), Some(r));
+            assert_eq!(unrank(r, 5), Some(perm.clone()));
+            r += 1;
+            if !next_permutation(&mut perm) {
+                break;
+            }
+        }
+        assert_eq!(r, 120);
+        assert_eq!(unrank(120, 5), None);
+        assert_eq!(perm, vec![0, 1, 2, 3, 4]);
+
+        assert!(!prev_permutation(&mut perm));
+        assert_eq!(perm, vec![4, 3, 2, 1, 0]);
+        assert!(prev_permutation(&mut perm));
+        assert_eq!(rank(&perm), Some(118));
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        let mut rng = rand::thread_rng();
+        for n in [0, 1, 2, 10, 34, 100] {
+            let mut perm: Vec<usize> = (0..n).collect();
+            perm.shuffle(&mut rng);
+            assert_eq!(
+                from_lehmer_code(&lehmer_code(&perm).unwrap()),
+                Some(perm.clone())
+            );
+            assert_eq!(
+                from_inversion_table(&inversion_table(&perm).unwrap()),
+                Some(perm.clone())
+            );
+            if n <= 34 {
+                // 34! < 2^128
+                let r = rank(&perm).unwrap();
+                assert_eq!(unrank(r, n), Some(perm.clone()));
+            }
+        }
+        // 35 個の逆順は 35! - 1 > u128::MAX
+        let reversed: Vec<usize> = (0..35).rev().collect();
+        assert_eq!(rank(&reversed), None);
+    }
+
+    #[test]
+    fn test_rejects_non_permutations() {
+        // 範囲外の値・重複・欠けている値
+        for perm in [&[1, 2, 3][..], &[0, 0, 1], &[0, 2, 2]] {
+            assert_eq!(lehmer_code(perm), None, "{:?}", perm);
+            assert_eq!(inversion_table(perm), None, "{:?}", perm);
+            assert_eq!(rank(perm), None, "{:?}", perm);
+        }
+        assert_eq!(lehmer_code(&[]), Some(vec![]));
+        assert_eq!(rank(&[]), Some(0));
+    }
+
+    #[test]
+    fn test_next_permutation_with_duplicates() {
+        let mut a = vec![1, 1, 2];
+        let mut seen = vec![a.clone()];
+        while next_permutation(&mut a) {
+            seen.push(a.clone());
+        }
+        assert_eq!(seen, vec![vec![1, 1, 2], vec![1, 2, 1], vec![2, 1, 1]]);
+    }
+}
@@ -0,0 +1,248 @@
+//! 部分列 a[l..r) の転倒数を何度も問い合わせる
+//! - オフライン: Mo's algorithm とフェニック木。クエリを並べ替え、区間の端を1つずつ動かす
+//!   O((n + q)√n log n)
+//! - オンライン: 平方分割。前計算 O(n√n log n)・メモリ O(n√n)、1クエリ O(√n log n)
+
+use crate::fenwick::{compress, count_inversion_with_fenwick, Fenwick};
+
+fn block_len(n: usize) -> usize {
+    ((n as f64).sqrt() as usize).max(1)
+}
+
+/// Mo's algorithm でクエリ [l, r) ごとの転倒数をまとめて求める
+/// - 答えはクエリと同じ順に返す
+pub fn range_inversions_offline<T: Ord>(a: &[T], queries: &[(usize, usize)]) -> Vec<i64> {
+    let n = a.len();
+    let (ranks, distinct) = compress(a);
+    let block = block_len(n);
+    // 並べ替えのキーで n - r を使うので、先にすべて確かめる
+    for &(l, r) in queries {
+        assert!(l <= r && r <= n, "区間 [{}, {}) が範囲外です", l, r);
+    }
+
+    // 左端のブロック順、ブロック内は右端を行き来する順（奇数ブロックは降順）
+    let mut order: Vec<usize> = (0..queries.len()).collect();
+    order.sort_by_key(|&k| {
+        let (l, r) = queries[k];
+        let b = l / block;
+        (b, if b.is_multiple_of(2) { r } else { n - r })
+    });
+
+    let mut fenwick = Fenwick::new(distinct);
+    let (mut cur_l, mut cur_r) = (0, 0);
+    let mut size = 0;
+    let mut count = 0;
+    let mut answers = vec![0; queries.len()];
+    for k in order {
+        let (l, r) = queries[k];
+        // 区間が空にならないよう、先に広げてから縮める
+        while cur_l > l {
+            cur_l -= 1;
+            let x = ranks[cur_l];
+            // 左端に加える要素は、区間内の自分より小さい要素と転倒する
+            count += fenwick.prefix_sum(x);
+            fenwick.add(x, 1);
+            size += 1;
+        }
+        while cur_r < r {
+            let x = ranks[cur_r];
+            // 右端に加える要素は、区間内の自分より大きい要素と転倒する
+            count += size - fenwick.prefix_sum(x + 1);
+            fenwick.add(x, 1);
+            size += 1;
+            cur_r += 1;
+        }
+        while cur_l < l {
+            let x = ranks[cur_l];
+            fenwick.add(x, -1);
+            size -= 1;
+            count -= fenwick.prefix_sum(x);
+            cur_l += 1;
+        }
+        while cur_r > r {
+            cur_r -= 1;
+            let x = ranks[cur_r];
+            fenwick.add(x, -1);
+            size -= 1;
+            count -= size - fenwick.prefix_sum(x + 1);
+        }
+        answers[k] = count;
+    }
+    answers
+}
+
+/// 平方分割による部分列の転倒数
+/// - 長さ B のブロックに分け、[l, r) を「端数 [l, bB)」と「ブロック境界から [bB, r)」に分けて数える
+pub struct RangeInversions {
+    ranks: Vec<usize>,
+    block: usize,
+    /// within[b][r - bB] = a[bB..r) の転倒数
+    within: Vec<Vec<i64>>,
+    /// less[k][v] = a[..kB) のうち順位が v 未満の要素の個数
+    less: Vec<Vec<u32>>,
+    /// sorted[k] = ブロック k の添字を (順位, 添字) の順に並べたもの
+    sorted: Vec<Vec<usize>>,
+}
+
+impl RangeInversions {
+    pub fn new<T: Ord>(a: &[T]) -> Self {
+        let n = a.len();
+        let (ranks, distinct) = compress(a);
+        let block = block_len(n);
+        let starts = n / block + 1;
+
+        let within = (0..starts)
+            .map(|b| {
+                let mut fenwick = Fenwick::new(distinct);
+                let mut row = Vec::with_capacity(n - b * block + 1);
+                row.push(0);
+                let mut count = 0;
+                for (seen, &x) in ranks[b * block..].iter().enumerate() {
+                    count += seen as i64 - fenwick.prefix_sum(x + 1);
+                    fenwick.add(x, 1);
+                    row.push(count);
+                }
+                row
+            })
+            .collect();
+
+        let mut less = Vec::with_capacity(starts);
+        let mut seen = vec![0u32; distinct];
+        for k in 0..starts {
+            let mut row = vec![0; distinct + 1];
+            for v in 0..distinct {
+                row[v + 1] = row[v] + seen[v];
+            }
+            less.push(row);
+            for &x in &ranks[k * block..((k + 1) * block).min(n)] {
+                seen[x] += 1;
+            }
+        }
+
+        let sorted = (0..n.div_ceil(block))
+            .map(|k| {
+                let mut idx: Vec<usize> = (k * block..((k + 1) * block).min(n)).collect();
+                idx.sort_by_key(|&i| ranks[i]);
+                idx
+            })
+            .collect();
+
+        Self {
+            ranks,
+            block,
+            within,
+            less,
+            sorted,
+        }
+    }
+
+    /// a[l..r) の転倒数
+    pub fn query(&self, l: usize, r: usize) -> i64 {
+        let n = self.ranks.len();
+        assert!(l <= r && r <= n, "区間 [{}, {}) が範囲外です", l, r);
+        let block = self.block;
+        // 短い区間はそのまま数える
+        if r - l <= 2 * block {
+            return count_inversion_with_fenwick(&self.ranks[l..r]);
+        }
+
+        // l 以上で最初のブロック境界 b と、r 以下で最後のブロック境界 e（b < e）
+        let b = l.div_ceil(block);
+        let e = r / block;
+        let head = &self.ranks[l..b * block];
+
+        // [bB, r) の中と、端数の中
+        let mut count = self.within[b][r - b * block] + count_inversion_with_fenwick(head);
+        // 端数 × ブロック [bB, eB)
+        for &x in head {
+            count += (self.less[e][x] - self.less[b][x]) as i64;
+        }
+        // 端数 × 右の端数 [eB, r)：どちらもブロック内の整列済みの添字から取り出して数える
+        if !head.is_empty() && e * block < r {
+            let head_sorted: Vec<usize> = self.sorted[b - 1]
+                .iter()
+                .filter(|&&i| i >= l)
+                .map(|&i| self.ranks[i])
+                .collect();
+            let mut p = 0;
+            for &j in self.sorted[e].iter().filter(|&&j| j < r) {
+                let y = self.ranks[j];
+                while p < head_sorted.len() && head_sorted[p] <= y {
+                    p += 1;
+                }
+                count += (head_sorted.len() - p) as i64;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naive;
+    use rand::Rng;
+
+    fn random_array(rng: &mut impl Rng, n: usize) -> Vec<i32> {
+        let max = rng.gen_range(1..20);
+        (0..n).map(|_| rng.gen_range(0..max)).collect()
+    }
+
+    #[test]
+    fn test_range_inversions_offline() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let n = rng.gen_range(0..120);
+            let a = random_array(&mut rng, n);
+            let queries: Vec<(usize, usize)> = (0..200)
+                .map(|_| {
+                    let l = rng.gen_range(0..=n);
+                    (l, rng.gen_range(l..=n))
+                })
+                .collect();
+            let answers = range_inversions_offline(&a, &queries);
+            for (&(l, r), &answer) in queries.iter().zip(&answers) {
+                assert_eq!(answer, naive(&a[l..r]), "[{}, {})", l, r);
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_inversions_online() {
+        let mut rng = rand::thread_rng();
+        for n in [0, 1, 2, 5, 16, 17, 50, 99] {
+            let a = random_array(&mut rng, n);
+            let ranges = RangeInversions::new(&a);
+            for l in 0..=n {
+                for r in l..=n {
+                    assert_eq!(ranges.query(l, r), naive(&a[l..r]), "[{}, {})", l, r);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_inversions_agree_on_large_input() {
+        let mut rng = rand::thread_rng();
+        let a = random_array(&mut rng, 3000);
+        let queries: Vec<(usize, usize)> = (0..300)
+            .map(|_| {
+                let l = rng.gen_range(0..3000);
+                (l, rng.gen_range(l..=3000))
+            })
+            .collect();
+        let ranges = RangeInversions::new(&a);
+        let offline = range_inversions_offline(&a, &queries);
+        for (&(l, r), &answer) in queries.iter().zip(&offline) {
+            assert_eq!(ranges.query(l, r), answer);
+            assert_eq!(answer, count_inversion_with_fenwick(&a[l..r]));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "区間 [0, 5) が範囲外です")]
+    fn test_offline_rejects_out_of_range_query() {
+        // 並べ替えより先に確かめるので、n - r のあふれではなくこのメッセージで止まる
+        range_inversions_offline(&[3, 1, 2], &[(0, 2), (0, 5)]);
+    }
+}
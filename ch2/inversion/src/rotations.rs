@@ -0,0 +1,67 @@
+//! 巡回シフトしたすべての列の転倒数
+//! - 先頭の要素 x を末尾へ回すと、x より小さい要素との転倒がなくなり、
+//!   x より大きい要素との転倒が新たにできる（同じ値とは転倒しない）
+//! - 最初の転倒数をマージソートで求め、あとは1回 O(log n) で更新する
+
+use crate::count_inversion_with_merge_sort;
+
+/// k 番目が a[k..] + a[..k] の転倒数になる列を返す
+pub fn rotation_inversions(a: &[i32]) -> Vec<i64> {
+    let n = a.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut sorted = a.to_vec();
+    let mut count = count_inversion_with_merge_sort(&mut sorted);
+
+    let mut counts = Vec::with_capacity(n);
+    counts.push(count);
+    for &x in &a[..n - 1] {
+        let smaller = sorted.partition_point(|&y| y < x);
+        let greater = n - sorted.partition_point(|&y| y <= x);
+        count += greater as i64 - smaller as i64;
+        counts.push(count);
+    }
+    counts
+}
+
+/// 転倒数が最小になる回転 (k, 転倒数)。同じなら k が小さい方
+pub fn best_rotation(a: &[i32]) -> Option<(usize, i64)> {
+    rotation_inversions(a)
+        .into_iter()
+        .enumerate()
+        .min_by_key(|&(k, count)| (count, k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naive;
+    use rand::Rng;
+
+    #[test]
+    fn test_rotation_inversions_1() {
+        // 3 1 2 → 1 2 3 → 2 3 1
+        assert_eq!(rotation_inversions(&[3, 1, 2]), vec![2, 0, 2]);
+        assert_eq!(best_rotation(&[3, 1, 2]), Some((1, 0)));
+        assert_eq!(best_rotation(&[]), None);
+    }
+
+    #[test]
+    fn test_rotation_inversions_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.gen_range(1..40);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..6)).collect();
+            let counts = rotation_inversions(&a);
+            for (k, &count) in counts.iter().enumerate() {
+                let mut rotated = a.clone();
+                rotated.rotate_left(k);
+                assert_eq!(count, naive(&rotated), "k = {}", k);
+            }
+            let (k, count) = best_rotation(&a).unwrap();
+            assert_eq!(count, *counts.iter().min().unwrap());
+            assert_eq!(counts[k], count);
+        }
+    }
+}
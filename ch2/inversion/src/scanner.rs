@@ -0,0 +1,228 @@
+//! 標準入力のトークン読み取り
+//! - 1行ずつバッファに読み、空白区切りのトークンを行・列の位置つきで返す
+//! - 行をまたいだ入力や、1行に全部並べた入力をどちらも受け付ける
+
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// 入力の誤り
+#[derive(Debug)]
+pub enum InputError {
+    Io(std::io::Error),
+    /// 値が足りない
+    UnexpectedEof {
+        expected: String,
+    },
+    /// 数として読めない
+    Invalid {
+        line: usize,
+        column: usize,
+        token: String,
+        expected: String,
+    },
+    /// 読み終えた後に余分な値がある
+    Trailing {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(e) => write!(f, "入力を読めません: {}", e),
+            InputError::UnexpectedEof { expected } => {
+                write!(f, "入力の終わりに達しました（{}がありません）", expected)
+            }
+            InputError::Invalid {
+                line,
+                column,
+                token,
+                expected,
+            } => write!(
+                f,
+                "{} 行 {} 列: {}を読めません: \"{}\"",
+                line, column, expected, token
+            ),
+            InputError::Trailing {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "{} 行 {} 列: 余分な値 \"{}\" があります",
+                line, column, token
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<std::io::Error> for InputError {
+    fn from(e: std::io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+/// 位置つきのトークン（行・列は 1 始まり、列は文字単位）
+#[derive(Debug, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+pub struct Scanner<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+    /// buf の中の読み出し位置（バイト）と、それに対応する列
+    pos: usize,
+    column: usize,
+}
+
+impl<R: BufRead> Scanner<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+            pos: 0,
+            column: 1,
+        }
+    }
+
+    /// 次のトークン。入力の終わりなら None
+    pub fn token(&mut self) -> Result<Option<Token>, InputError> {
+        loop {
+            // 空白を読み飛ばす（列は文字単位で数える）
+            let rest = &self.buf[self.pos..];
+            let skipped = rest.len() - rest.trim_start().len();
+            self.column += rest[..skipped].chars().count();
+            self.pos += skipped;
+
+            if self.pos < self.buf.len() {
+                let rest = &self.buf[self.pos..];
+                let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let token = Token {
+                    text: rest[..len].to_string(),
+                    line: self.line,
+                    column: self.column,
+                };
+                self.column += token.text.chars().count();
+                self.pos += len;
+                return Ok(Some(token));
+            }
+
+            // 行を読み終えたので次の行へ
+            self.buf.clear();
+            self.pos = 0;
+            self.column = 1;
+            if self.reader.read_line(&mut self.buf)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+        }
+    }
+
+    /// 次のトークンを T として読む
+    /// - expected は誤りの表示に使う名前（誤りのときだけ作る）
+    pub fn next<T: FromStr>(&mut self, expected: impl FnOnce() -> String) -> Result<T, InputError> {
+        let Some(token) = self.token()? else {
+            return Err(InputError::UnexpectedEof {
+                expected: expected(),
+            });
+        };
+        token.text.parse().map_err(|_| InputError::Invalid {
+            line: token.line,
+            column: token.column,
+            token: token.text,
+            expected: expected(),
+        })
+    }
+
+    /// 入力が残っていないことを確かめる
+    pub fn finish(&mut self) -> Result<(), InputError> {
+        match self.token()? {
+            None => Ok(()),
+            Some(token) => Err(InputError::Trailing {
+                line: token.line,
+                column: token.column,
+                token: token.text,
+            }),
+        }
+    }
+}
+
+/// 「n と、続く n 個の整数」を読む
+pub fn read_sequence<R: BufRead>(reader: R) -> Result<Vec<i64>, InputError> {
+    let mut scanner = Scanner::new(reader);
+    let n: usize = scanner.next(|| "要素数".to_string())?;
+    let a = (0..n)
+        .map(|i| scanner.next(|| format!("{} 番目の値", i + 1)))
+        .collect::<Result<Vec<i64>, _>>()?;
+    scanner.finish()?;
+    Ok(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_with_positions() {
+        let mut scanner = Scanner::new("  3\n\n1  -2\tあ 4\n".as_bytes());
+        let mut tokens = Vec::new();
+        while let Some(token) = scanner.token().unwrap() {
+            tokens.push((token.text, token.line, token.column));
+        }
+        assert_eq!(
+            tokens,
+            vec![
+                ("3".to_string(), 1, 3),
+                ("1".to_string(), 3, 1),
+                ("-2".to_string(), 3, 4),
+                ("あ".to_string(), 3, 7),
+                ("4".to_string(), 3, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_sequence() {
+        assert_eq!(
+            read_sequence("5\n2 3\n8\n6 1".as_bytes()).unwrap(),
+            vec![2, 3, 8, 6, 1]
+        );
+        assert_eq!(
+            read_sequence("2 9000000000000000000 -9000000000000000000".as_bytes()).unwrap(),
+            vec![9_000_000_000_000_000_000, -9_000_000_000_000_000_000]
+        );
+        assert_eq!(read_sequence("0\n".as_bytes()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_read_sequence_errors() {
+        let message = |input: &str| read_sequence(input.as_bytes()).unwrap_err().to_string();
+        assert_eq!(
+            message("3\n1 x 2"),
+            "2 行 3 列: 2 番目の値を読めません: \"x\""
+        );
+        assert_eq!(
+            message("3\n1 2"),
+            "入力の終わりに達しました（3 番目の値がありません）"
+        );
+        assert_eq!(
+            message("2\n1 2\n  3"),
+            "3 行 3 列: 余分な値 \"3\" があります"
+        );
+        assert_eq!(message("-1"), "1 行 1 列: 要素数を読めません: \"-1\"");
+        assert_eq!(
+            message(""),
+            "入力の終わりに達しました（要素数がありません）"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! 条件つきの転倒数（a[i] > c·a[j] など）
+//! - `count_inversion_with_merge_sort` の「>」を一般の条件 pred(a[i], a[j]) に置き換えたもの
+//! - 条件が大小と単調なら、マージの前に左右の整列済みの列を2つのポインタで数えられる
+//! - c は有理数 p/q で受け取り、q·a[i] > p·a[j] を i128 で比べるので、
+//!   i32 の全範囲と i64 の p, q であふれず、誤差もない
+
+/// pred(a[i], a[j]) を満たす i < j の組の数を数え、arr を昇順に並べる
+/// - pred は x について大きいほど、y について小さいほど成り立ちやすいこと
+///   （pred(x, y) かつ x ≤ x' かつ y' ≤ y なら pred(x', y')）
+pub fn count_pairs_with_merge_sort<F>(arr: &mut [i32], pred: &F) -> i64
+where
+    F: Fn(i32, i32) -> bool,
+{
+    let mut buf = vec![0; arr.len()];
+    sort_and_count(arr, &mut buf, pred, false)
+}
+
+/// `y_descending` なら pred は y について大きいほど成り立ちやすいとし、右を降順に見る
+fn sort_and_count<F>(arr: &mut [i32], buf: &mut [i32], pred: &F, y_descending: bool) -> i64
+where
+    F: Fn(i32, i32) -> bool,
+{
+    let len = arr.len();
+    if len <= 1 {
+        return 0;
+    }
+    let mid = len / 2;
+    let mut count = sort_and_count(&mut arr[..mid], &mut buf[..mid], pred, y_descending)
+        + sort_and_count(&mut arr[mid..], &mut buf[mid..], pred, y_descending);
+
+    // 右の要素を成り立ちにくくなる順に見ると、条件を満たす左の要素の境界は右へしか動かない
+    let (left, right) = arr.split_at(mid);
+    let mut boundary = 0;
+    for k in 0..right.len() {
+        let y = if y_descending {
+            right[right.len() - 1 - k]
+        } else {
+            right[k]
+        };
+        while boundary < mid && !pred(left[boundary], y) {
+            boundary += 1;
+        }
+        // left[boundary..] はすべて条件を満たす
+        count += (mid - boundary) as i64;
+    }
+
+    // 数え終わってから通常どおりマージする
+    let (mut left_index, mut right_index) = (0, mid);
+    for slot in buf.iter_mut() {
+        if right_index == len || (left_index < mid && arr[left_index] <= arr[right_index]) {
+            *slot = arr[left_index];
+            left_index += 1;
+        } else {
+            *slot = arr[right_index];
+            right_index += 1;
+        }
+    }
+    arr.copy_from_slice(buf);
+    count
+}
+
+/// 有理数の係数 c = numerator / denominator（denominator > 0 にそろえて持つ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scale {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Scale {
+    /// 分母が 0 なら None
+    pub fn new(numerator: i64, denominator: i64) -> Option<Self> {
+        let (numerator, denominator) = (numerator as i128, denominator as i128);
+        match denominator.signum() {
+            0 => None,
+            sign => Some(Self {
+                numerator: sign * numerator,
+                denominator: sign * denominator,
+            }),
+        }
+    }
+}
+
+impl From<i64> for Scale {
+    fn from(c: i64) -> Self {
+        Self {
+            numerator: c as i128,
+            denominator: 1,
+        }
+    }
+}
+
+/// a[i] > c·a[j] となる i < j の組の数
+/// - c は整数か `Scale::new(3, 2)`（= 1.5）のような有理数
+/// - c < 0 では条件が a[j] について逆向きに単調になるので、右を降順に見る
+pub fn count_scaled_inversions(arr: &mut [i32], c: impl Into<Scale>) -> i64 {
+    let Scale {
+        numerator: p,
+        denominator: q,
+    } = c.into();
+    // x > (p/q)·y ⟺ q·x > p·y（q > 0）
+    let pred = |x: i32, y: i32| q * x as i128 > p * y as i128;
+    let mut buf = vec![0; arr.len()];
+    sort_and_count(arr, &mut buf, &pred, p < 0)
+}
+
+/// a[i] > 2·a[j] となる i < j の組の数（significant inversion）
+pub fn count_significant_inversions(arr: &mut [i32]) -> i64 {
+    count_scaled_inversions(arr, 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_inversion_with_merge_sort;
+    use rand::Rng;
+
+    fn naive_pairs(a: &[i32], pred: impl Fn(i32, i32) -> bool) -> i64 {
+        let mut count = 0;
+        for i in 0..a.len() {
+            for j in i + 1..a.len() {
+                count += pred(a[i], a[j]) as i64;
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_count_significant_inversions() {
+        let mut a = vec![1, 3, 2, 3, 1];
+        assert_eq!(count_significant_inversions(&mut a), 2);
+        assert_eq!(a, vec![1, 1, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_plain_predicate_matches_merge_sort() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.gen_range(0..60);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(-10..10)).collect();
+            assert_eq!(
+                count_pairs_with_merge_sort(&mut a.clone(), &|x, y| x > y),
+                count_inversion_with_merge_sort(&mut a.clone())
+            );
+            assert_eq!(
+                count_scaled_inversions(&mut a.clone(), 1),
+                naive_pairs(&a, |x, y| x > y)
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_scaled_inversions_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300 {
+            let n = rng.gen_range(0..60);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(-50..50)).collect();
+            let c = rng.gen_range(-5..5);
+            let expected = naive_pairs(&a, |x, y| x as i64 > c * y as i64);
+            assert_eq!(count_scaled_inversions(&mut a.clone(), c), expected);
+        }
+    }
+
+    #[test]
+    fn test_count_scaled_inversions_rational() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300 {
+            let n = rng.gen_range(0..60);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(-50..50)).collect();
+            let (p, q) = (rng.gen_range(-9..10), rng.gen_range(-4..5));
+            let Some(c) = Scale::new(p, q) else {
+                assert_eq!(q, 0);
+                continue;
+            };
+            // x > (p/q)·y を浮動小数点を使わずに比べる
+            let expected = naive_pairs(&a, |x, y| {
+                if q > 0 {
+                    q * x as i64 > p * y as i64
+                } else {
+                    q * (x as i64) < p * y as i64
+                }
+            });
+            assert_eq!(count_scaled_inversions(&mut a.clone(), c), expected);
+        }
+        // 1.5 倍を超える転倒: 4 > 1.5·2, 4 > 1.5·1, 3 > 1.5·1, 2 > 1.5·1（3 > 1.5·2 は等しいので数えない）
+        let mut a = vec![4, 3, 2, 1];
+        assert_eq!(
+            count_scaled_inversions(&mut a, Scale::new(3, 2).unwrap()),
+            4
+        );
+        assert_eq!(Scale::new(1, 0), None);
+        assert_eq!(Scale::new(3, -2), Scale::new(-3, 2));
+    }
+
+    #[test]
+    fn test_extreme_values_do_not_overflow() {
+        let a = vec![i32::MAX, i32::MIN, i32::MAX, -1, i32::MIN, 0];
+        for c in [0, 1, 2, -1, i64::MAX, i64::MIN] {
+            let expected = naive_pairs(&a, |x, y| x as i128 > c as i128 * y as i128);
+            assert_eq!(count_scaled_inversions(&mut a.clone(), c), expected);
+        }
+        let c = Scale::new(i64::MIN, i64::MIN).unwrap();
+        let expected = naive_pairs(&a, |x, y| x > y);
+        assert_eq!(count_scaled_inversions(&mut a.clone(), c), expected);
+    }
+
+    #[test]
+    fn test_user_predicate() {
+        // 差が 3 より大きい転倒だけを数える
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.gen_range(0..60);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..20)).collect();
+            let pred = |x: i32, y: i32| x - y > 3;
+            assert_eq!(
+                count_pairs_with_merge_sort(&mut a.clone(), &pred),
+                naive_pairs(&a, pred)
+            );
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! 隣接交換で列 A を列 B に並べ替える最小回数
+//! - A の各要素に B での行き先を割り当てると、行き先の列の転倒数が最小回数になる
+//! - 同じ値は A と B で現れる順に対応させる（入れ替えても得をしないため）
+
+use crate::fenwick::count_inversion_with_fenwick;
+use std::fmt;
+
+/// B が A の並べ替えになっていない
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotPermutation;
+
+impl fmt::Display for NotPermutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B が A の並べ替えになっていません")
+    }
+}
+
+impl std::error::Error for NotPermutation {}
+
+/// A の i 番目の要素の B での位置
+fn target_positions<T: Ord>(a: &[T], b: &[T]) -> Result<Vec<usize>, NotPermutation> {
+    if a.len() != b.len() {
+        return Err(NotPermutation);
+    }
+    // 安定ソートなので、同じ値は現れる順に並ぶ
+    let mut a_order: Vec<usize> = (0..a.len()).collect();
+    a_order.sort_by(|&i, &j| a[i].cmp(&a[j]));
+    let mut b_order: Vec<usize> = (0..b.len()).collect();
+    b_order.sort_by(|&i, &j| b[i].cmp(&b[j]));
+
+    let mut target = vec![0; a.len()];
+    for (&i, &j) in a_order.iter().zip(&b_order) {
+        if a[i] != b[j] {
+            return Err(NotPermutation);
+        }
+        target[i] = j;
+    }
+    Ok(target)
+}
+
+/// A を B にする隣接交換の最小回数
+pub fn min_adjacent_swaps<T: Ord>(a: &[T], b: &[T]) -> Result<i64, NotPermutation> {
+    Ok(count_inversion_with_fenwick(&target_positions(a, b)?))
+}
+
+/// A を B にする最小回数の隣接交換の列
+/// - p は位置 p と p + 1 の交換を表し、A に順に適用すると B になる
+/// - 挿入ソートで1つの交換が1つの転倒を解消するので、O(n + 交換回数)
+pub fn adjacent_swap_sequence<T: Ord>(a: &[T], b: &[T]) -> Result<Vec<usize>, NotPermutation> {
+    let mut target = target_positions(a, b)?;
+    let mut swaps = Vec::new();
+    for i in 1..target.len() {
+        let mut p = i;
+        while p > 0 && target[p - 1] > target[p] {
+            target.swap(p - 1, p);
+            swaps.push(p - 1);
+            p -= 1;
+        }
+    }
+    Ok(swaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    #[test]
+    fn test_min_adjacent_swaps() {
+        assert_eq!(min_adjacent_swaps(&[1, 2, 3], &[3, 2, 1]), Ok(3));
+        // 同じ値は順に対応させる: b, a, a → a, a, b は2回
+        assert_eq!(
+            min_adjacent_swaps(&["b", "a", "a"], &["a", "a", "b"]),
+            Ok(2)
+        );
+        assert_eq!(min_adjacent_swaps::<i32>(&[], &[]), Ok(0));
+    }
+
+    #[test]
+    fn test_not_permutation() {
+        assert_eq!(
+            min_adjacent_swaps(&[1, 2, 2], &[1, 1, 2]),
+            Err(NotPermutation)
+        );
+        assert_eq!(min_adjacent_swaps(&[1, 2], &[1, 2, 3]), Err(NotPermutation));
+        assert_eq!(
+            adjacent_swap_sequence(&[1, 2], &[1, 3]),
+            Err(NotPermutation)
+        );
+    }
+
+    #[test]
+    fn test_adjacent_swap_sequence_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.gen_range(0..30);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..5)).collect();
+            let mut b = a.clone();
+            b.shuffle(&mut rng);
+
+            let swaps = adjacent_swap_sequence(&a, &b).unwrap();
+            assert_eq!(swaps.len() as i64, min_adjacent_swaps(&a, &b).unwrap());
+            let mut c = a.clone();
+            for &p in &swaps {
+                c.swap(p, p + 1);
+            }
+            assert_eq!(c, b);
+
+            // 小さい場合は全探索（BFS）の最短手数と比べる
+            if n <= 6 {
+                assert_eq!(swaps.len(), bfs_distance(&a, &b));
+            }
+        }
+    }
+
+    fn bfs_distance(a: &[i32], b: &[i32]) -> usize {
+        use std::collections::{HashSet, VecDeque};
+        let mut seen = HashSet::from([a.to_vec()]);
+        let mut queue = VecDeque::from([(a.to_vec(), 0)]);
+        while let Some((v, d)) = queue.pop_front() {
+            if v == b {
+                return d;
+            }
+            for p in 0..v.len().saturating_sub(1) {
+                let mut w = v.clone();
+                w.swap(p, p + 1);
+                if seen.insert(w.clone()) {
+                    queue.push_back((w, d + 1));
+                }
+            }
+        }
+        unreachable!("b は a の並べ替え")
+    }
+}
@@ -0,0 +1,104 @@
+//! 要素ごとの転倒数（転倒ベクトル）
+//! - 各位置 i について、右にある i より小さい要素の個数（Lehmer code）と、
+//!   左にある i より大きい要素の個数を求める
+//! - どちらの合計も転倒数に等しい
+//! - `count_inversion_with_merge_sort` と同じマージの途中で、元の添字ごとに数を振り分ける
+
+/// 要素ごとの転倒数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InversionVectors {
+    /// smaller_right[i] = #{ j > i | a[j] < a[i] }（Lehmer code）
+    pub smaller_right: Vec<usize>,
+    /// greater_left[j] = #{ i < j | a[i] > a[j] }
+    pub greater_left: Vec<usize>,
+}
+
+impl InversionVectors {
+    /// 転倒数
+    pub fn total(&self) -> i64 {
+        self.smaller_right.iter().sum::<usize>() as i64
+    }
+}
+
+/// 添字の列 order[..] を値の順にマージソートしながら、要素ごとの転倒数を数える
+fn sort_and_count<T: Ord>(
+    a: &[T],
+    order: &mut [usize],
+    buf: &mut [usize],
+    vectors: &mut InversionVectors,
+) {
+    let len = order.len();
+    if len <= 1 {
+        return;
+    }
+    let mid = len / 2;
+    sort_and_count(a, &mut order[..mid], &mut buf[..mid], vectors);
+    sort_and_count(a, &mut order[mid..], &mut buf[mid..], vectors);
+
+    let mut left_index = 0;
+    let mut right_index = mid;
+    for slot in buf.iter_mut() {
+        // 安定ソートのため、同じ場合は左を採用
+        if right_index == len || (left_index < mid && a[order[left_index]] <= a[order[right_index]])
+        {
+            // それまでに採用した右側の要素はすべてこの要素より小さい
+            let i = order[left_index];
+            vectors.smaller_right[i] += right_index - mid;
+            *slot = i;
+            left_index += 1;
+        } else {
+            // 左側の残りの要素はすべてこの要素より大きい
+            let j = order[right_index];
+            vectors.greater_left[j] += mid - left_index;
+            *slot = j;
+            right_index += 1;
+        }
+    }
+    order.copy_from_slice(buf);
+}
+
+/// 要素ごとの転倒数を O(n log n) で求める（入力は書き換えない）
+pub fn inversion_vectors<T: Ord>(a: &[T]) -> InversionVectors {
+    let n = a.len();
+    let mut vectors = InversionVectors {
+        smaller_right: vec![0; n],
+        greater_left: vec![0; n],
+    };
+    let mut order: Vec<usize> = (0..n).collect();
+    let mut buf = vec![0; n];
+    sort_and_count(a, &mut order, &mut buf, &mut vectors);
+    vectors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naive;
+    use rand::Rng;
+
+    #[test]
+    fn test_inversion_vectors_1() {
+        let vectors = inversion_vectors(&[2, 3, 8, 6, 1]);
+        assert_eq!(vectors.smaller_right, vec![1, 1, 2, 1, 0]);
+        assert_eq!(vectors.greater_left, vec![0, 0, 0, 1, 4]);
+        assert_eq!(vectors.total(), 5);
+    }
+
+    #[test]
+    fn test_inversion_vectors_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300 {
+            let n = rng.gen_range(0..50);
+            let a: Vec<i32> = (0..n).map(|_| rng.gen_range(0..8)).collect();
+            let vectors = inversion_vectors(&a);
+            for i in 0..n {
+                let smaller = a[i + 1..].iter().filter(|&&x| x < a[i]).count();
+                let greater = a[..i].iter().filter(|&&x| x > a[i]).count();
+                assert_eq!(vectors.smaller_right[i], smaller);
+                assert_eq!(vectors.greater_left[i], greater);
+            }
+            assert_eq!(vectors.total(), naive(&a));
+            assert_eq!(vectors.greater_left.iter().sum::<usize>() as i64, naive(&a));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! 2要素の和の判定の各方式の速さを比べる
+//! - 使い方: cargo run --release --bin bench [最大の指数（3 以上 8 以下、既定 7）]
+//! - 値はすべて偶数、x は奇数にして、どの方式も最後まで調べる最悪の場合を測る
+
+use q2_3_8::two_sum::{solve_binary_search, solve_btree, solve_hash, solve_two_pointer};
+use rand::Rng;
+use std::time::Instant;
+
+/// 最初に測る長さ 10^3 の指数
+const MIN_EXPONENT: u32 = 3;
+/// 最大の指数の上限（10^8 個で入力だけで 800MB になる）
+const MAX_EXPONENT: u32 = 8;
+
+type SolveFn = fn(i64, &[i64]) -> bool;
+
+const STRATEGIES: [(&str, SolveFn); 4] = [
+    ("BTreeSet", solve_btree),
+    ("HashSet", solve_hash),
+    ("ソート+2ポインタ", solve_two_pointer),
+    ("ソート+二分探索", solve_binary_search),
+];
+
+/// 1回の実行時間（ミリ秒）の中央値
+fn median_millis(solve: SolveFn, x: i64, a: &[i64], iterations: usize) -> f64 {
+    let mut millis: Vec<f64> = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            let found = solve(x, a);
+            let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+            assert!(!found, "x は奇数なので組は見つからない");
+            elapsed
+        })
+        .collect();
+    millis.sort_by(f64::total_cmp);
+    millis[millis.len() / 2]
+}
+
+/// 引数を読めなければ、使い方を表示して終了コード 2 で終わる
+fn usage(message: String) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "使い方: bench [最大の指数（{} 以上 {} 以下、既定 7）]",
+        MIN_EXPONENT, MAX_EXPONENT
+    );
+    std::process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() > 1 {
+        usage(format!("余分な引数があります: {}", args[1]));
+    }
+    let max_exponent: u32 = args.first().map_or(7, |s| {
+        s.parse()
+            .unwrap_or_else(|_| usage(format!("数値を指定してください: {}", s)))
+    });
+    // 範囲外だと何も測らないか、入力を確保できない
+    if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&max_exponent) {
+        usage(format!(
+            "最大の指数は {} 以上 {} 以下を指定してください: {}",
+            MIN_EXPONENT, MAX_EXPONENT, max_exponent
+        ));
+    }
+    let mut rng = rand::thread_rng();
+
+    for exponent in MIN_EXPONENT..=max_exponent {
+        let n = 10usize.pow(exponent);
+        let a: Vec<i64> = (0..n)
+            .map(|_| 2 * rng.gen_range(-(n as i64)..n as i64))
+            .collect();
+        let x = 2 * rng.gen_range(-(n as i64)..n as i64) + 1;
+        // 短い入力は何回か測って中央値を取る
+        let iterations = if n <= 100_000 { 5 } else { 1 };
+
+        println!("リストの長さ: {}", n);
+        let results: Vec<(&str, f64)> = STRATEGIES
+            .iter()
+            .map(|&(name, solve)| (name, median_millis(solve, x, &a, iterations)))
+            .collect();
+        let fastest = results
+            .iter()
+            .map(|&(_, millis)| millis)
+            .fold(f64::INFINITY, f64::min);
+        for (name, millis) in results {
+            let mark = if millis == fastest { " ← 最速" } else { "" };
+            println!("  {}: {:.3} ms{}", name, millis, mark);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! 和が x になる k 要素（k-SUM）
+//! - 3-SUM: ソートして1つ目を固定し、残りを2つのポインタで探す（O(n²)）
+//! - 4-SUM: 2要素の和を全部作り、前半の組と後半の組を和で突き合わせる（半分全列挙、O(n² log n)）
+//! - 一般の k: 1つずつ固定して k-1 の問題に帰着し、k = 2 で2ポインタ（O(n^(k-1))）
+//! - どれも判定・個数・列挙ができ、値の組（昇順）としてそれぞれ1回だけ数える
+//! - 値は i64、和は i128 で計算するのであふれない
+
+use crate::merge_sort::merge_sort;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+/// 見つけた値の組を受け取る。Break を返したらそこで探索をやめる
+type Visit<'a> = dyn FnMut(&[i64]) -> ControlFlow<()> + 'a;
+
+fn sorted(a: &[i64]) -> Vec<i64> {
+    let mut s = a.to_vec();
+    merge_sort(&mut s);
+    s
+}
+
+fn visit_three_sum(x: i64, a: &[i64], visit: &mut Visit) -> ControlFlow<()> {
+    let s = sorted(a);
+    let n = s.len();
+    let x = x as i128;
+    for i in 0..n.saturating_sub(2) {
+        // 同じ値を1つ目にした組は数え済み
+        if i > 0 && s[i] == s[i - 1] {
+            continue;
+        }
+        let (mut l, mut r) = (i + 1, n - 1);
+        while l < r {
+            let sum = s[i] as i128 + s[l] as i128 + s[r] as i128;
+            if sum < x {
+                l += 1;
+            } else if sum > x {
+                r -= 1;
+            } else {
+                visit(&[s[i], s[l], s[r]])?;
+                // 同じ値の組を飛ばす
+                let (left, right) = (s[l], s[r]);
+                while l < r && s[l] == left {
+                    l += 1;
+                }
+                while l < r && s[r] == right {
+                    r -= 1;
+                }
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// 値の組を1回ずつ数えるため、整列済みの列で各値の「最初の出現から連続した位置」だけを使う
+/// - 位置 p は、p が値の最初の出現か、直前の位置 p-1 も組に使っているときに使える
+fn is_first_use(s: &[i64], p: usize, previous: Option<usize>) -> bool {
+    p == 0 || s[p - 1] != s[p] || previous == Some(p - 1)
+}
+
+fn visit_four_sum(x: i64, a: &[i64], visit: &mut Visit) -> ControlFlow<()> {
+    let s = sorted(a);
+    let n = s.len();
+
+    // 前半の組 (i, j) を和ごとに j の昇順で持つ
+    let mut halves: HashMap<i128, Vec<(usize, usize)>> = HashMap::new();
+    for j in 0..n {
+        for i in 0..j {
+            if is_first_use(&s, i, None) && is_first_use(&s, j, Some(i)) {
+                halves
+                    .entry(s[i] as i128 + s[j] as i128)
+                    .or_default()
+                    .push((i, j));
+            }
+        }
+    }
+
+    // 後半の組 (k, l) ごとに、j < k の前半の組を探す
+    for l in 0..n {
+        for k in 0..l {
+            if !is_first_use(&s, l, Some(k)) {
+                continue;
+            }
+            let rest = x as i128 - s[k] as i128 - s[l] as i128;
+            let Some(lefts) = halves.get(&rest) else {
+                continue;
+            };
+            // k が値の最初の出現でなければ、前半は j = k - 1 で終わっていなければならない
+            let end = lefts.partition_point(|&(_, j)| j < k);
+            let begin = if is_first_use(&s, k, None) {
+                0
+            } else {
+                lefts.partition_point(|&(_, j)| j + 1 < k)
+            };
+            for &(i, j) in &lefts[begin..end] {
+                visit(&[s[i], s[j], s[k], s[l]])?;
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// 整列済みの s から k 個選んで和を target にする（prefix はそれまでに選んだ値）
+fn visit_k_sum_sorted(
+    s: &[i64],
+    k: usize,
+    target: i128,
+    prefix: &mut Vec<i64>,
+    visit: &mut Visit,
+) -> ControlFlow<()> {
+    if k == 0 {
+        if target == 0 {
+            visit(prefix)?;
+        }
+        return ControlFlow::Continue(());
+    }
+    if s.len() < k {
+        return ControlFlow::Continue(());
+    }
+    if k == 1 {
+        if let Ok(value) = i64::try_from(target) {
+            if s.binary_search(&value).is_ok() {
+                prefix.push(value);
+                visit(prefix)?;
+                prefix.pop();
+            }
+        }
+        return ControlFlow::Continue(());
+    }
+    if k == 2 {
+        let (mut l, mut r) = (0, s.len() - 1);
+        while l < r {
+            let sum = s[l] as i128 + s[r] as i128;
+            if sum < target {
+                l += 1;
+            } else if sum > target {
+                r -= 1;
+            } else {
+                prefix.extend([s[l], s[r]]);
+                let flow = visit(prefix);
+                prefix.truncate(prefix.len() - 2);
+                flow?;
+                let (left, right) = (s[l], s[r]);
+                while l < r && s[l] == left {
+                    l += 1;
+                }
+                while l < r && s[r] == right {
+                    r -= 1;
+                }
+            }
+        }
+        return ControlFlow::Continue(());
+    }
+
+    let largest = *s.last().unwrap() as i128;
+    for i in 0..=s.len() - k {
+        if i > 0 && s[i] == s[i - 1] {
+            continue;
+        }
+        // 残りはすべて s[i] 以上なので、最小の和が target を超えたら終わり
+        if s[i] as i128 * k as i128 > target {
+            break;
+        }
+        // 残りを最大値で埋めても届かなければ次へ
+        if s[i] as i128 + largest * ((k - 1) as i128) < target {
+            continue;
+        }
+        prefix.push(s[i]);
+        let flow = visit_k_sum_sorted(&s[i + 1..], k - 1, target - s[i] as i128, prefix, visit);
+        prefix.pop();
+        flow?;
+    }
+    ControlFlow::Continue(())
+}
+
+fn visit_k_sum(k: usize, x: i64, a: &[i64], visit: &mut Visit) -> ControlFlow<()> {
+    visit_k_sum_sorted(&sorted(a), k, x as i128, &mut Vec::with_capacity(k), visit)
+}
+
+fn exists(run: impl FnOnce(&mut Visit) -> ControlFlow<()>) -> bool {
+    run(&mut |_| ControlFlow::Break(())).is_break()
+}
+
+fn count(run: impl FnOnce(&mut Visit) -> ControlFlow<()>) -> usize {
+    let mut count = 0;
+    let _ = run(&mut |_| {
+        count += 1;
+        ControlFlow::Continue(())
+    });
+    count
+}
+
+/// 見つけた値の組を辞書順に並べて返す
+fn collect(run: impl FnOnce(&mut Visit) -> ControlFlow<()>) -> Vec<Vec<i64>> {
+    let mut tuples = Vec::new();
+    let _ = run(&mut |tuple| {
+        tuples.push(tuple.to_vec());
+        ControlFlow::Continue(())
+    });
+    tuples.sort();
+    tuples
+}
+
+/// 和が x になる3要素があるか
+pub fn three_sum_exists(x: i64, a: &[i64]) -> bool {
+    exists(|visit| visit_three_sum(x, a, visit))
+}
+
+/// 和が x になる3要素の値の組の個数
+pub fn three_sum_count(x: i64, a: &[i64]) -> usize {
+    count(|visit| visit_three_sum(x, a, visit))
+}
+
+/// 和が x になる3要素の値の組（各組は昇順、全体は辞書順）
+pub fn three_sum(x: i64, a: &[i64]) -> Vec<[i64; 3]> {
+    collect(|visit| visit_three_sum(x, a, visit))
+        .into_iter()
+        .map(|t| [t[0], t[1], t[2]])
+        .collect()
+}
+
+/// 和が x になる4要素があるか
+pub fn four_sum_exists(x: i64, a: &[i64]) -> bool {
+    exists(|visit| visit_four_sum(x, a, visit))
+}
+
+/// 和が x になる4要素の値の組の個数
+pub fn four_sum_count(x: i64, a: &[i64]) -> usize {
+    count(|visit| visit_four_sum(x, a, visit))
+}
+
+/// 和が x になる4要素の値の組（各組は昇順、全体は辞書順）
+pub fn four_sum(x: i64, a: &[i64]) -> Vec<[i64; 4]> {
+    collect(|visit| visit_four_sum(x, a, visit))
+        .into_iter()
+        .map(|t| [t[0], t[1], t[2], t[3]])
+        .collect()
+}
+
+/// 和が x になる k 要素があるか
+pub fn k_sum_exists(k: usize, x: i64, a: &[i64]) -> bool {
+    exists(|visit| visit_k_sum(k, x, a, visit))
+}
+
+/// 和が x になる k 要素の値の組の個数
+pub fn k_sum_count(k: usize, x: i64, a: &[i64]) -> usize {
+    count(|visit| visit_k_sum(k, x, a, visit))
+}
+
+/// 和が x になる k 要素の値の組（各組は昇順、全体は辞書順）
+pub fn k_sum(k: usize, x: i64, a: &[i64]) -> Vec<Vec<i64>> {
+    collect(|visit| visit_k_sum(k, x, a, visit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::collections::BTreeSet;
+
+    /// すべての位置の組を調べ、値の組（昇順）の集合にする
+    fn brute_force(k: usize, x: i64, a: &[i64]) -> Vec<Vec<i64>> {
+        fn rec(
+            a: &[i64],
+            start: usize,
+            k: usize,
+            x: i128,
+            chosen: &mut Vec<i64>,
+            found: &mut BTreeSet<Vec<i64>>,
+        ) {
+            if k == 0 {
+                if chosen.iter().map(|&v| v as i128).sum::<i128>() == x {
+                    let mut tuple = chosen.clone();
+                    tuple.sort();
+                    found.insert(tuple);
+                }
+                return;
+            }
+            for i in start..a.len() {
+                chosen.push(a[i]);
+                rec(a, i + 1, k - 1, x, chosen, found);
+                chosen.pop();
+            }
+        }
+        let mut found = BTreeSet::new();
+        rec(a, 0, k, x as i128, &mut Vec::new(), &mut found);
+        found.into_iter().collect()
+    }
+
+    #[test]
+    fn test_three_sum() {
+        let a = vec![-1, 0, 1, 2, -1, -4];
+        assert_eq!(three_sum(0, &a), vec![[-1, -1, 2], [-1, 0, 1]]);
+        assert_eq!(three_sum_count(0, &a), 2);
+        assert!(three_sum_exists(0, &a));
+        assert!(!three_sum_exists(100, &a));
+        assert!(three_sum(0, &[0, 0]).is_empty());
+        assert_eq!(three_sum(0, &[0, 0, 0, 0]), vec![[0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_four_sum() {
+        let a = vec![1, 0, -1, 0, -2, 2];
+        assert_eq!(
+            four_sum(0, &a),
+            vec![[-2, -1, 1, 2], [-2, 0, 0, 2], [-1, 0, 0, 1]]
+        );
+        assert_eq!(four_sum_count(0, &a), 3);
+        assert_eq!(four_sum(8, &[2; 6]), vec![[2, 2, 2, 2]]);
+        assert!(!four_sum_exists(8, &[2, 2, 2]));
+    }
+
+    #[test]
+    fn test_k_sum_small_k() {
+        let a = vec![3, 1, 3, 2];
+        assert_eq!(k_sum(0, 0, &a), vec![Vec::<i64>::new()]);
+        assert_eq!(k_sum(1, 3, &a), vec![vec![3]]);
+        assert_eq!(k_sum(2, 6, &a), vec![vec![3, 3]]);
+        assert_eq!(k_sum_count(5, 9, &a), 0);
+    }
+
+    #[test]
+    fn test_extreme_values_do_not_overflow() {
+        let a = vec![i64::MAX, i64::MAX, i64::MIN, i64::MIN, -1, 0, 1];
+        for x in [-2, -1, 0, 1, i64::MAX, i64::MIN] {
+            assert_eq!(
+                three_sum(x, &a)
+                    .into_iter()
+                    .map(|t| t.to_vec())
+                    .collect::<Vec<_>>(),
+                brute_force(3, x, &a)
+            );
+            assert_eq!(
+                four_sum(x, &a)
+                    .into_iter()
+                    .map(|t| t.to_vec())
+                    .collect::<Vec<_>>(),
+                brute_force(4, x, &a)
+            );
+            assert_eq!(k_sum(5, x, &a), brute_force(5, x, &a));
+        }
+    }
+
+    #[test]
+    fn test_against_brute_force_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300 {
+            let n = rng.gen_range(0..12);
+            let a: Vec<i64> = (0..n).map(|_| rng.gen_range(-4..5)).collect();
+            let x = rng.gen_range(-8..9);
+
+            let expected3 = brute_force(3, x, &a);
+            let three: Vec<Vec<i64>> = three_sum(x, &a).iter().map(|t| t.to_vec()).collect();
+            assert_eq!(three, expected3, "a = {:?}, x = {}", a, x);
+            assert_eq!(three_sum_count(x, &a), expected3.len());
+            assert_eq!(three_sum_exists(x, &a), !expected3.is_empty());
+
+            let expected4 = brute_force(4, x, &a);
+            let four: Vec<Vec<i64>> = four_sum(x, &a).iter().map(|t| t.to_vec()).collect();
+            assert_eq!(four, expected4, "a = {:?}, x = {}", a, x);
+            assert_eq!(four_sum_count(x, &a), expected4.len());
+            assert_eq!(four_sum_exists(x, &a), !expected4.is_empty());
+
+            for k in 1..=5 {
+                let expected = brute_force(k, x, &a);
+                assert_eq!(k_sum(k, x, &a), expected, "k = {}, a = {:?}", k, a);
+                assert_eq!(k_sum_count(k, x, &a), expected.len());
+                assert_eq!(k_sum_exists(k, x, &a), !expected.is_empty());
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod k_sum;
+pub mod merge_sort;
+pub mod two_sum;
@@ -1,15 +1,4 @@
-use std::collections::BTreeSet;
-
-fn solve(x: i32, a: &[i32]) -> bool {
-    let mut s = BTreeSet::new();
-    for &num in a {
-        if s.contains(&(x - num)) {
-            return true;
-        }
-        s.insert(num);
-    }
-    false
-}
+use q2_3_8::two_sum::{all_pairs, count_pairs, solve};
 
 fn main() {
     let x = 10;
@@ -17,9 +6,14 @@ fn main() {
     let a = vec![1, 2, 3, 7, 8];
 
     println!("{}", if solve(x, &a) { "Yes" } else { "No" });
+    println!("組の数: {}", count_pairs(x, &a));
+    for (i, j) in all_pairs(x, &a) {
+        println!("a[{}] + a[{}] = {} + {} = {}", i, j, a[i], a[j], x);
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
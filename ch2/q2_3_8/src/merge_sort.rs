@@ -0,0 +1,68 @@
+//! マージソート（ch4/marge_sort と同じもの。要素の型を一般化した）
+
+fn merge<T: Copy + Ord>(arr: &mut [T], mid: usize) {
+    let left = arr[0..mid].to_vec();
+    let right = arr[mid..].to_vec();
+
+    let mut i = 0;
+
+    let mut left_idx = 0;
+    let mut right_idx = 0;
+    while left_idx < left.len() && right_idx < right.len() {
+        if left[left_idx] <= right[right_idx] {
+            // 左から採用
+            arr[i] = left[left_idx];
+            left_idx += 1;
+        } else {
+            // 右から採用
+            arr[i] = right[right_idx];
+            right_idx += 1;
+        }
+        i += 1;
+    }
+    while left_idx < left.len() {
+        arr[i] = left[left_idx];
+        left_idx += 1;
+        i += 1;
+    }
+    while right_idx < right.len() {
+        arr[i] = right[right_idx];
+        right_idx += 1;
+        i += 1;
+    }
+}
+
+pub fn merge_sort<T: Copy + Ord>(arr: &mut [T]) {
+    /* 基底段階 */
+    if arr.len() <= 1 {
+        return;
+    }
+    /* 再帰段階 */
+    // 分割
+    let mid = arr.len() / 2;
+    let (left, right) = arr.split_at_mut(mid);
+    // 統治
+    merge_sort(left);
+    merge_sort(right);
+    // 結合
+    merge(arr, mid);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_sort_reversed() {
+        let mut arr = vec![5, 4, 3, 2, 1];
+        merge_sort(&mut arr);
+        assert_eq!(arr, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_merge_sort_extremes() {
+        let mut arr = vec![u64::MAX, 0, 7, u64::MAX, 1];
+        merge_sort(&mut arr);
+        assert_eq!(arr, vec![0, 1, 7, u64::MAX, u64::MAX]);
+    }
+}
@@ -0,0 +1,307 @@
+//! 和が x になる2要素（CLRS 演習 2.3-8）
+//! - すべての基本整数型で使えるよう、相方 x - a[j] を checked_sub で求める
+//! - 引き算が型の範囲を超えるなら、そのような相方は存在しないので読み飛ばせる
+//! - 判定だけなら、BTreeSet・HashSet・ソート＋2ポインタ・ソート＋二分探索の4通りがある
+//!   （速さの比較は `cargo run --release --bin bench`）
+
+use crate::merge_sort::merge_sort;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::hash::Hash;
+
+/// 2要素の和を探せる整数型
+pub trait Integer: Copy + Ord + Hash {
+    /// x - self。型の範囲に収まらなければ None
+    fn complement(self, x: Self) -> Option<Self>;
+
+    /// self + other と x の比較（和があふれても正しく比べる）
+    fn sum_cmp(self, other: Self, x: Self) -> Ordering;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn complement(self, x: Self) -> Option<Self> {
+                    x.checked_sub(self)
+                }
+
+                fn sum_cmp(self, other: Self, x: Self) -> Ordering {
+                    match self.checked_add(other) {
+                        Some(sum) => sum.cmp(&x),
+                        // 上にあふれるのは other が正のときだけ
+                        None if other > 0 as $t => Ordering::Greater,
+                        None => Ordering::Less,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// 和が x になる最初の組 (i, j)（i < j）
+/// - 左から見ていき、j が最も小さく、その中で i が最も小さい組
+pub fn first_pair<T: Integer>(x: T, a: &[T]) -> Option<(usize, usize)> {
+    // 値 → 最初に現れた位置
+    let mut first = BTreeMap::new();
+    for (j, &num) in a.iter().enumerate() {
+        if let Some(&i) = num.complement(x).and_then(|y| first.get(&y)) {
+            return Some((i, j));
+        }
+        first.entry(num).or_insert(j);
+    }
+    None
+}
+
+/// 和が x になるすべての組 (i, j)（i < j）を j、i の順に並べて返す
+pub fn all_pairs<T: Integer>(x: T, a: &[T]) -> Vec<(usize, usize)> {
+    // 値 → 現れた位置の列
+    let mut positions: BTreeMap<T, Vec<usize>> = BTreeMap::new();
+    let mut pairs = Vec::new();
+    for (j, &num) in a.iter().enumerate() {
+        if let Some(is) = num.complement(x).and_then(|y| positions.get(&y)) {
+            pairs.extend(is.iter().map(|&i| (i, j)));
+        }
+        positions.entry(num).or_default().push(j);
+    }
+    pairs
+}
+
+/// 和が x になる組 (i, j)（i < j）の個数
+pub fn count_pairs<T: Integer>(x: T, a: &[T]) -> usize {
+    // 値 → 現れた回数
+    let mut counts: BTreeMap<T, usize> = BTreeMap::new();
+    let mut count = 0;
+    for &num in a {
+        count += num
+            .complement(x)
+            .and_then(|y| counts.get(&y))
+            .copied()
+            .unwrap_or(0);
+        *counts.entry(num).or_default() += 1;
+    }
+    count
+}
+
+pub fn solve<T: Integer>(x: T, a: &[T]) -> bool {
+    first_pair(x, a).is_some()
+}
+
+/// BTreeSet で判定する（O(n log n)）
+pub fn solve_btree<T: Integer>(x: T, a: &[T]) -> bool {
+    let mut s = BTreeSet::new();
+    for &num in a {
+        if num.complement(x).is_some_and(|y| s.contains(&y)) {
+            return true;
+        }
+        s.insert(num);
+    }
+    false
+}
+
+/// HashSet で判定する（期待 O(n)）
+pub fn solve_hash<T: Integer>(x: T, a: &[T]) -> bool {
+    let mut s = HashSet::with_capacity(a.len());
+    for &num in a {
+        if num.complement(x).is_some_and(|y| s.contains(&y)) {
+            return true;
+        }
+        s.insert(num);
+    }
+    false
+}
+
+/// マージソートしてから両端から2つのポインタを寄せる（CLRS の想定解、O(n log n)）
+pub fn solve_two_pointer<T: Integer>(x: T, a: &[T]) -> bool {
+    let mut sorted = a.to_vec();
+    merge_sort(&mut sorted);
+    if sorted.is_empty() {
+        return false;
+    }
+    let (mut i, mut j) = (0, sorted.len() - 1);
+    while i < j {
+        match sorted[i].sum_cmp(sorted[j], x) {
+            Ordering::Equal => return true,
+            // 和が小さいので小さい方を増やす
+            Ordering::Less => i += 1,
+            Ordering::Greater => j -= 1,
+        }
+    }
+    false
+}
+
+/// マージソートしてから各要素の相方を右側で二分探索する（O(n log n)）
+pub fn solve_binary_search<T: Integer>(x: T, a: &[T]) -> bool {
+    let mut sorted = a.to_vec();
+    merge_sort(&mut sorted);
+    (0..sorted.len()).any(|i| {
+        sorted[i]
+            .complement(x)
+            .is_some_and(|y| sorted[i + 1..].binary_search(&y).is_ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_first_pair() {
+        assert_eq!(first_pair(10, &[1, 2, 3, 7, 8]), Some((2, 3)));
+        assert_eq!(first_pair(6, &[1, 2, 3]), None);
+        // 同じ値を2回使うには2つの要素が必要
+        assert_eq!(first_pair(6, &[3, 1, 3]), Some((0, 2)));
+        assert_eq!(first_pair(6, &[3, 1]), None);
+    }
+
+    #[test]
+    fn test_all_pairs_and_count_with_duplicates() {
+        let a = vec![2, 2, 2, 0, 4];
+        assert_eq!(all_pairs(4, &a), vec![(0, 1), (0, 2), (1, 2), (3, 4)]);
+        assert_eq!(count_pairs(4, &a), 4);
+        assert_eq!(count_pairs(100, &a), 0);
+        assert!(all_pairs(100, &a).is_empty());
+    }
+
+    #[test]
+    fn test_variants_agree_with_brute_force() {
+        let a = vec![3, -1, 4, 1, -5, 9, 2, 6, 5, 3, 5];
+        for x in -10..20 {
+            let mut expected = Vec::new();
+            for j in 0..a.len() {
+                for i in 0..j {
+                    if a[i] + a[j] == x {
+                        expected.push((i, j));
+                    }
+                }
+            }
+            assert_eq!(all_pairs(x, &a), expected);
+            assert_eq!(count_pairs(x, &a), expected.len());
+            assert_eq!(first_pair(x, &a), expected.first().copied());
+            assert_eq!(solve(x, &a), !expected.is_empty());
+            for strategy in [
+                solve_btree,
+                solve_hash,
+                solve_two_pointer,
+                solve_binary_search,
+            ] {
+                assert_eq!(strategy(x, &a), !expected.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        // i32 のまま x - num を計算するとあふれる
+        assert!(solve(i32::MIN, &[i32::MIN, 0]));
+        assert!(!solve(i32::MAX, &[i32::MIN, -1]));
+        assert!(solve(-1, &[i32::MIN, i32::MAX]));
+        assert_eq!(count_pairs(i64::MIN, &[i64::MIN / 2; 3]), 3);
+        assert!(solve(255u8, &[200, 55]));
+        assert!(!solve(10u8, &[200, 66]));
+        assert!(solve(u128::MAX, &[u128::MAX, 0]));
+    }
+
+    /// 全組を調べる O(n²) の答え（和があふれる組は x にならない）
+    fn brute_force<T: Integer + CheckedAdd>(x: T, a: &[T]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for j in 0..a.len() {
+            for i in 0..j {
+                if a[i].add(a[j]) == Some(x) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    trait CheckedAdd: Sized {
+        fn add(self, other: Self) -> Option<Self>;
+    }
+
+    macro_rules! impl_checked_add {
+        ($($t:ty),*) => {
+            $(
+                impl CheckedAdd for $t {
+                    fn add(self, other: Self) -> Option<Self> {
+                        self.checked_add(other)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_checked_add!(i8, i32, i64, i128, u8, u64, u128);
+
+    fn check<T: Integer + CheckedAdd + std::fmt::Debug>(
+        x: T,
+        a: &[T],
+    ) -> Result<(), TestCaseError> {
+        let expected = brute_force(x, a);
+        prop_assert_eq!(&all_pairs(x, a), &expected);
+        prop_assert_eq!(count_pairs(x, a), expected.len());
+        prop_assert_eq!(first_pair(x, a), expected.first().copied());
+        prop_assert_eq!(solve(x, a), !expected.is_empty());
+        prop_assert_eq!(solve_btree(x, a), !expected.is_empty());
+        prop_assert_eq!(solve_hash(x, a), !expected.is_empty());
+        prop_assert_eq!(solve_two_pointer(x, a), !expected.is_empty());
+        prop_assert_eq!(solve_binary_search(x, a), !expected.is_empty());
+        Ok(())
+    }
+
+    /// 端の値が出やすい値の戦略
+    macro_rules! extreme {
+        ($t:ty) => {
+            prop_oneof![
+                Just(<$t>::MIN),
+                Just(<$t>::MAX),
+                Just(<$t>::MIN + 1),
+                Just(<$t>::MAX - 1),
+                Just(0 as $t),
+                Just(<$t>::MAX / 2),
+                Just(<$t>::MIN / 2),
+                any::<$t>(),
+            ]
+        };
+    }
+
+    proptest! {
+        #[test]
+        fn prop_i8(x in any::<i8>(), a in prop::collection::vec(any::<i8>(), 0..40)) {
+            check(x, &a)?;
+        }
+
+        #[test]
+        fn prop_u8(x in any::<u8>(), a in prop::collection::vec(any::<u8>(), 0..40)) {
+            check(x, &a)?;
+        }
+
+        #[test]
+        fn prop_i32(x in extreme!(i32), a in prop::collection::vec(extreme!(i32), 0..30)) {
+            check(x, &a)?;
+        }
+
+        #[test]
+        fn prop_i64(x in extreme!(i64), a in prop::collection::vec(extreme!(i64), 0..30)) {
+            check(x, &a)?;
+        }
+
+        #[test]
+        fn prop_u64(x in extreme!(u64), a in prop::collection::vec(extreme!(u64), 0..30)) {
+            check(x, &a)?;
+        }
+
+        #[test]
+        fn prop_i128(x in extreme!(i128), a in prop::collection::vec(extreme!(i128), 0..30)) {
+            check(x, &a)?;
+        }
+
+        #[test]
+        fn prop_u128(x in extreme!(u128), a in prop::collection::vec(extreme!(u128), 0..30)) {
+            check(x, &a)?;
+        }
+    }
+}
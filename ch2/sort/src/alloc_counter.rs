@@ -0,0 +1,103 @@
+//! 確保回数・確保量・ヒープ使用量の増分のピークを数えるグローバルアロケータ
+//! - 実際の確保は `System` に任せ、その前後でカウンタを更新するだけ
+//! - 計測したい区間の前で `reset`、後で `stats` を呼ぶ
+//! - 数えるのはヒープだけ。プロセスの常駐量は `resident` で測る
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// `reset` した時点の使用量（ピークはここからの増分で報告する）
+static BASELINE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+fn record_alloc(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+    let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            // 伸縮も1回の確保として数え、使用量は差分だけ動かす
+            CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// 計測区間のメモリ統計
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// 確保（realloc を含む）の回数
+    pub allocations: usize,
+    /// 確保したバイト数の合計
+    pub allocated_bytes: usize,
+    /// 計測開始時点からのヒープ使用量の最大増分
+    pub peak_bytes: usize,
+}
+
+/// 計測区間を開始する
+pub fn reset() {
+    let current = CURRENT_BYTES.load(Ordering::Relaxed);
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    ALLOCATED_BYTES.store(0, Ordering::Relaxed);
+    BASELINE_BYTES.store(current, Ordering::Relaxed);
+    PEAK_BYTES.store(current, Ordering::Relaxed);
+}
+
+/// `reset` からの統計を返す
+pub fn stats() -> AllocStats {
+    AllocStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        peak_bytes: PEAK_BYTES
+            .load(Ordering::Relaxed)
+            .saturating_sub(BASELINE_BYTES.load(Ordering::Relaxed)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // テストは並列に走り、カウンタは全スレッドで共有なので下限だけを確かめる
+    #[test]
+    fn test_counts_allocation() {
+        reset();
+        let v: Vec<u8> = Vec::with_capacity(4096);
+        let s = stats();
+        assert!(s.allocations >= 1);
+        assert!(s.allocated_bytes >= 4096);
+        drop(v);
+    }
+}
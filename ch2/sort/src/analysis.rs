@@ -0,0 +1,155 @@
+//! 計測結果から計算量を推定する
+//! - 両対数で直線回帰し、t = c·n^k の指数 k を求める
+//! - n, n lg n, n² の各モデルを当てはめ、決定係数 R² が最も高いものを選ぶ
+
+/// 推定に必要な計測点の数（2点では直線が必ず通ってしまう）
+const MIN_POINTS: usize = 3;
+/// これより R² が低い当てはめは、どのモデルとも判定しない
+pub const MIN_R_SQUARED: f64 = 0.9;
+
+/// 計算量のモデル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    Linear,
+    NLogN,
+    Quadratic,
+}
+
+impl Model {
+    const ALL: [Model; 3] = [Model::Linear, Model::NLogN, Model::Quadratic];
+
+    fn eval(self, n: f64) -> f64 {
+        match self {
+            Model::Linear => n,
+            Model::NLogN => n * n.log2(),
+            Model::Quadratic => n * n,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Model::Linear => "n",
+            Model::NLogN => "n lg n",
+            Model::Quadratic => "n²",
+        }
+    }
+}
+
+/// 当てはめの結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    /// 両対数回帰で求めた指数
+    pub exponent: f64,
+    /// 両対数回帰の決定係数
+    pub exponent_r_squared: f64,
+    /// 最もよく当てはまるモデル
+    pub best: Model,
+    /// `best` の決定係数（両対数上で計算）
+    pub best_r_squared: f64,
+}
+
+/// 決定係数 R² = 1 - 残差平方和 / 全平方和
+fn r_squared(ys: &[f64], predicted: &[f64]) -> f64 {
+    let mean = ys.iter().sum::<f64>() / ys.len() as f64;
+    let ss_tot: f64 = ys.iter().map(|y| (y - mean).powi(2)).sum();
+    let ss_res: f64 = ys.iter().zip(predicted).map(|(y, p)| (y - p).powi(2)).sum();
+    if ss_tot == 0.0 {
+        return if ss_res == 0.0 { 1.0 } else { 0.0 };
+    }
+    1.0 - ss_res / ss_tot
+}
+
+/// `(要素数, 時間)` の組から計算量を推定する
+/// - 時間が 0 以下の点や、要素数が 2 未満の点（lg n が 0 以下）は対数が取れないので除く
+/// - 使える点が3つ未満なら `None`
+pub fn fit(points: &[(f64, f64)]) -> Option<Fit> {
+    let points: Vec<(f64, f64)> = points
+        .iter()
+        .copied()
+        .filter(|&(n, t)| n >= 2.0 && t > 0.0)
+        .collect();
+    if points.len() < MIN_POINTS {
+        return None;
+    }
+    let xs: Vec<f64> = points.iter().map(|&(n, _)| n.ln()).collect();
+    let ys: Vec<f64> = points.iter().map(|&(_, t)| t.ln()).collect();
+    let len = points.len() as f64;
+
+    // ln t = a + k ln n の最小二乗
+    let mean_x = xs.iter().sum::<f64>() / len;
+    let mean_y = ys.iter().sum::<f64>() / len;
+    let sxx: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = xs
+        .iter()
+        .zip(&ys)
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let exponent = sxy / sxx;
+    let intercept = mean_y - exponent * mean_x;
+    let predicted: Vec<f64> = xs.iter().map(|x| intercept + exponent * x).collect();
+    let exponent_r_squared = r_squared(&ys, &predicted);
+
+    // 各モデルについて ln t = ln c + ln f(n) の ln c だけを最小二乗で決める
+    let (best, best_r_squared) = Model::ALL
+        .iter()
+        .map(|&model| {
+            let log_f: Vec<f64> = points.iter().map(|&(n, _)| model.eval(n).ln()).collect();
+            let log_c = ys.iter().zip(&log_f).map(|(y, f)| y - f).sum::<f64>() / len;
+            let predicted: Vec<f64> = log_f.iter().map(|f| log_c + f).collect();
+            (model, r_squared(&ys, &predicted))
+        })
+        .fold((Model::Linear, f64::NEG_INFINITY), |acc, cur| {
+            if cur.1 > acc.1 {
+                cur
+            } else {
+                acc
+            }
+        });
+
+    Some(Fit {
+        exponent,
+        exponent_r_squared,
+        best,
+        best_r_squared,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes() -> Vec<f64> {
+        (10..20).map(|i| 2f64.powi(i)).collect()
+    }
+
+    #[test]
+    fn test_fit_n_log_n() {
+        let points: Vec<(f64, f64)> = sizes().iter().map(|&n| (n, 3e-6 * n * n.log2())).collect();
+        let fit = fit(&points).unwrap();
+        assert_eq!(fit.best, Model::NLogN);
+        assert!((fit.best_r_squared - 1.0).abs() < 1e-9);
+        assert!(fit.exponent > 1.0 && fit.exponent < 1.2);
+    }
+
+    #[test]
+    fn test_fit_quadratic() {
+        let points: Vec<(f64, f64)> = sizes().iter().map(|&n| (n, 1e-9 * n * n)).collect();
+        let fit = fit(&points).unwrap();
+        assert_eq!(fit.best, Model::Quadratic);
+        assert!((fit.exponent - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fit_needs_three_points() {
+        assert_eq!(fit(&[(1024.0, 1.0)]), None);
+        assert_eq!(fit(&[(1024.0, 1.0), (2048.0, 0.0)]), None);
+        assert_eq!(fit(&[(1024.0, 1.0), (2048.0, 2.0)]), None);
+        // n = 1 では lg n = 0 なので n lg n の対数が取れない
+        assert_eq!(fit(&[(1.0, 1.0), (2.0, 2.0), (4.0, 4.0)]), None);
+        let fit = fit(&[(2.0, 2.0), (4.0, 4.0), (8.0, 8.0)]).unwrap();
+        assert!(fit.best_r_squared.is_finite());
+    }
+}
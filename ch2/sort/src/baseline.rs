@@ -0,0 +1,257 @@
+//! ベンチマーク結果のベースライン保存と比較
+//! - 反復ごとの時間を JSON に保存し、後の実行と比べる
+//! - 雑音で誤検出しないよう、中央値の変化率に加えて Mann–Whitney の U 検定で有意性を見る
+
+use serde::{Deserialize, Serialize};
+use special::erfc;
+use std::io;
+use std::path::Path;
+
+/// 1アルゴリズム・1要素数ぶんの計測結果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub algorithm: String,
+    pub length: usize,
+    /// 反復ごとの時間（ミリ秒）
+    pub millis: Vec<f64>,
+}
+
+/// ベースラインファイルの中身
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub records: Vec<Record>,
+}
+
+impl Baseline {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn find(&self, algorithm: &str, length: usize) -> Option<&Record> {
+        self.records
+            .iter()
+            .find(|r| r.algorithm == algorithm && r.length == length)
+    }
+}
+
+fn median(xs: &[f64]) -> f64 {
+    let mut sorted = xs.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// 正確な分布で p 値を求める標本数の合計の上限（これを超えたら正規近似）
+const EXACT_LIMIT: usize = 50;
+
+/// Mann–Whitney の U 検定（片側）
+/// - 対立仮説は「`current` の方が `base` より大きい（遅い）」
+/// - 標本が少ないときは、順位の割り当てをすべて数えた正確な p 値を返す
+/// - 多いときは、同順位の補正と連続性補正をした正規近似で p 値を返す
+pub fn mann_whitney_greater(base: &[f64], current: &[f64]) -> f64 {
+    let (n1, n2) = (base.len(), current.len());
+    if n1 == 0 || n2 == 0 {
+        return 1.0;
+    }
+
+    // 全体を並べて順位をつける（同値は平均順位）
+    let mut all: Vec<(f64, bool)> = base
+        .iter()
+        .map(|&x| (x, false))
+        .chain(current.iter().map(|&x| (x, true)))
+        .collect();
+    all.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let total = all.len();
+    // 平均順位は 0.5 刻みなので、2倍して整数で持つ
+    let mut doubled_ranks = Vec::with_capacity(total);
+    let mut doubled_rank_sum = 0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < total {
+        let mut j = i;
+        while j < total && all[j].0 == all[i].0 {
+            j += 1;
+        }
+        // 順位は 1 始まりで、[i, j) の平均順位の2倍
+        let doubled_rank = i + j + 1;
+        let ties = (j - i) as f64;
+        tie_term += ties * ties * ties - ties;
+        for &(_, is_current) in &all[i..j] {
+            doubled_ranks.push(doubled_rank);
+            if is_current {
+                doubled_rank_sum += doubled_rank;
+            }
+        }
+        i = j;
+    }
+
+    if total <= EXACT_LIMIT {
+        return exact_upper_tail(&doubled_ranks, n2, doubled_rank_sum);
+    }
+
+    let (n1, n2, n) = (n1 as f64, n2 as f64, total as f64);
+    let u = doubled_rank_sum as f64 / 2.0 - n2 * (n2 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    let z = (u - mean - 0.5) / variance.sqrt();
+    // 上側確率 1 - Φ(z)
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// 順位（2倍）の中から n2 個を選ぶすべての選び方のうち、和が observed 以上になる割合
+/// - 帰無仮説のもとでは、どの n2 個が `current` になるのも同じ確率
+fn exact_upper_tail(doubled_ranks: &[usize], n2: usize, observed: usize) -> f64 {
+    let max: usize = doubled_ranks.iter().sum();
+    // ways[c][s]: c 個選んで和が s になる選び方の数
+    let mut ways = vec![vec![0u128; max + 1]; n2 + 1];
+    ways[0][0] = 1;
+    for &r in doubled_ranks {
+        for c in (1..=n2).rev() {
+            for s in (r..=max).rev() {
+                ways[c][s] += ways[c - 1][s - r];
+            }
+        }
+    }
+    let all: u128 = ways[n2].iter().sum();
+    let tail: u128 = ways[n2][observed..].iter().sum();
+    tail as f64 / all as f64
+}
+
+/// 比較結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub algorithm: String,
+    pub length: usize,
+    /// 中央値の変化率（正なら遅くなった）
+    pub relative_change: f64,
+    pub p_value: f64,
+    /// 変化率が閾値を超え、かつ有意に遅くなった
+    pub regressed: bool,
+}
+
+/// `current` の各記録をベースラインの同じアルゴリズム・要素数の記録と比べる
+/// - ベースラインに無い組は比較しない
+pub fn compare(
+    baseline: &Baseline,
+    current: &Baseline,
+    threshold: f64,
+    alpha: f64,
+) -> Vec<Comparison> {
+    current
+        .records
+        .iter()
+        .filter_map(|record| {
+            let base = baseline.find(&record.algorithm, record.length)?;
+            let base_median = median(&base.millis);
+            let relative_change = if base_median > 0.0 {
+                median(&record.millis) / base_median - 1.0
+            } else {
+                0.0
+            };
+            let p_value = mann_whitney_greater(&base.millis, &record.millis);
+            Some(Comparison {
+                algorithm: record.algorithm.clone(),
+                length: record.length,
+                relative_change,
+                p_value,
+                regressed: relative_change > threshold && p_value < alpha,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(algorithm: &str, millis: &[f64]) -> Record {
+        Record {
+            algorithm: algorithm.to_string(),
+            length: 1024,
+            millis: millis.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_mann_whitney_clear_shift() {
+        let base = [10.0, 11.0, 10.5, 10.2, 10.8, 10.1, 10.9, 10.4];
+        let slower = [13.0, 12.5, 13.2, 12.8, 13.1, 12.9, 13.4, 12.7];
+        assert!(mann_whitney_greater(&base, &slower) < 0.01);
+        assert!(mann_whitney_greater(&slower, &base) > 0.99);
+    }
+
+    #[test]
+    fn test_mann_whitney_exact_small_samples() {
+        // 完全に分かれたときの p 値は 1 / C(n1 + n2, n2)
+        assert_eq!(mann_whitney_greater(&[1.0], &[10.0]), 0.5);
+        assert!((mann_whitney_greater(&[1.0, 2.0], &[10.0, 20.0]) - 1.0 / 6.0).abs() < 1e-12);
+        assert!((mann_whitney_greater(&[1.0, 2.0, 3.0], &[10.0, 20.0, 30.0]) - 0.05).abs() < 1e-12);
+        let base = [1.0, 2.0, 3.0, 4.0];
+        let slower = [10.0, 20.0, 30.0, 40.0];
+        assert!((mann_whitney_greater(&base, &slower) - 1.0 / 70.0).abs() < 1e-12);
+        // 同順位があっても、割り当てを数えれば正確に出る
+        assert!((mann_whitney_greater(&[1.0, 2.0], &[2.0, 3.0]) - 2.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mann_whitney_large_samples_use_approximation() {
+        let base: Vec<f64> = (0..40).map(|i| 10.0 + i as f64 * 0.01).collect();
+        let slower: Vec<f64> = (0..40).map(|i| 12.0 + i as f64 * 0.01).collect();
+        assert!(mann_whitney_greater(&base, &slower) < 1e-6);
+        assert!(mann_whitney_greater(&slower, &base) > 0.99);
+    }
+
+    #[test]
+    fn test_mann_whitney_identical() {
+        let xs = [5.0, 5.0, 5.0];
+        assert_eq!(mann_whitney_greater(&xs, &xs), 1.0);
+    }
+
+    #[test]
+    fn test_compare_detects_regression_only_when_significant() {
+        let baseline = Baseline {
+            records: vec![
+                record("a", &[10.0, 10.2, 9.9, 10.1, 10.0, 9.8]),
+                record("b", &[10.0, 10.2, 9.9, 10.1, 10.0, 9.8]),
+            ],
+        };
+        let current = Baseline {
+            records: vec![
+                record("a", &[12.0, 12.1, 11.9, 12.2, 12.0, 11.8]),
+                // 中央値は上がるが、ばらつきに埋もれている
+                record("b", &[8.0, 14.0, 9.0, 13.0, 10.5, 10.6]),
+                record("c", &[1.0]),
+            ],
+        };
+        let result = compare(&baseline, &current, 0.05, 0.05);
+        assert_eq!(result.len(), 2);
+        assert!(result[0].regressed);
+        assert!((result[0].relative_change - 0.2).abs() < 1e-9);
+        assert!(!result[1].regressed);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let baseline = Baseline {
+            records: vec![record("a", &[1.5, 2.5])],
+        };
+        let path = std::env::temp_dir().join(format!("sort_baseline_{}.json", std::process::id()));
+        baseline.save(&path).unwrap();
+        let loaded = Baseline::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, baseline);
+    }
+}
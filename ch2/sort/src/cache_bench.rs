@@ -0,0 +1,95 @@
+//! キャッシュ階層の境界をまたいで要素数を変えるベンチマーク
+//! - L1, L2, L3 の容量の前後と、L3 を大きく超える DRAM 領域で計測する
+//! - キャッシュオブリビアスなファネルソートと、キャッシュを意識したハイブリッドソートを比べる
+
+use crate::{funnelsort::funnelsort, hybrid_bottom_up_merge_sort, mean, measure};
+use rand::Rng;
+
+/// キャッシュの階層名と容量（バイト）
+/// - Linux の sysfs から読み、読めない場合は一般的なサーバの値を使う
+fn cache_levels() -> Vec<(String, usize)> {
+    let mut levels = Vec::new();
+    for index in 0.. {
+        let dir = format!("/sys/devices/system/cpu/cpu0/cache/index{}", index);
+        let read = |name: &str| std::fs::read_to_string(format!("{}/{}", dir, name));
+        let (Ok(level), Ok(kind), Ok(size)) = (read("level"), read("type"), read("size")) else {
+            break;
+        };
+        // 命令キャッシュはデータの局所性と関係ないので除く
+        if kind.trim() == "Instruction" {
+            continue;
+        }
+        let size = size.trim();
+        let bytes = match size.strip_suffix('K') {
+            Some(kib) => kib.parse::<usize>().ok().map(|k| k * 1024),
+            None => match size.strip_suffix('M') {
+                Some(mib) => mib.parse::<usize>().ok().map(|m| m * 1024 * 1024),
+                None => size.parse().ok(),
+            },
+        };
+        if let Some(bytes) = bytes {
+            levels.push((format!("L{}", level.trim()), bytes));
+        }
+    }
+    if levels.is_empty() {
+        levels = vec![
+            ("L1".to_string(), 32 * 1024),
+            ("L2".to_string(), 1024 * 1024),
+            ("L3".to_string(), 32 * 1024 * 1024),
+        ];
+    }
+    levels
+}
+
+/// 計測する要素数（i32 の個数）とその説明
+/// - 各キャッシュ容量の 1/4, 1/2, 1, 2 倍と、最大のキャッシュの 4, 16 倍
+fn sizes() -> Vec<(usize, String)> {
+    let levels = cache_levels();
+    let elem = std::mem::size_of::<i32>();
+    let mut sizes = Vec::new();
+    for (name, bytes) in &levels {
+        for (num, den) in [(1, 4), (1, 2), (1, 1), (2, 1)] {
+            sizes.push((
+                bytes * num / den / elem,
+                format!("{} x{}/{}", name, num, den),
+            ));
+        }
+    }
+    let largest = levels.iter().map(|&(_, bytes)| bytes).max().unwrap_or(0);
+    for factor in [4, 16] {
+        sizes.push((
+            largest * factor / elem,
+            format!("DRAM (最大キャッシュ x{})", factor),
+        ));
+    }
+    sizes.sort_by_key(|&(n, _)| n);
+    sizes.dedup_by_key(|(n, _)| *n);
+    sizes
+}
+
+/// `cache [iterations]`: キャッシュ境界ごとの1要素あたりの時間を表示する
+pub fn run(iterations: usize) {
+    let mut rng = rand::thread_rng();
+    for (name, bytes) in cache_levels() {
+        println!("{}: {} KiB", name, bytes / 1024);
+    }
+    for (n, label) in sizes() {
+        let original: Vec<i32> = (0..n).map(|_| rng.gen_range(0..1000000)).collect();
+        let mut funnel = Vec::with_capacity(iterations);
+        let mut hybrid = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            funnel.push(measure(&original, funnelsort).millis);
+            hybrid.push(measure(&original, |a| hybrid_bottom_up_merge_sort(a, 16)).millis);
+        }
+        // 1要素あたりのナノ秒で比べる
+        let per_elem = |millis: &[f64]| mean(millis) * 1e6 / n as f64;
+        println!(
+            "要素数: {} ({}), ファネルソート: {:.2}ns/要素, ハイブリッドソート: {:.2}ns/要素, 比: {:.2}",
+            n,
+            label,
+            per_elem(&funnel),
+            per_elem(&hybrid),
+            per_elem(&funnel) / per_elem(&hybrid)
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! キャッシュオブリビアスな遅延ファネルソート（Brodal–Fagerberg の lazy funnelsort）
+//! - 入力を n^(1/3) 個の区間に分けて再帰的にソートし、k-ファネルで k 本を一度にマージする
+//! - k-ファネルは二分木状のマージ器で、高さ h の節点の出力バッファは (2^h)^(3/2) 要素
+//! - バッファは van Emde Boas 順で1本の配列に並べるので、キャッシュの大きさを知らなくても
+//!   どの階層のキャッシュでも局所性が効く
+//! - バッファが空になったときだけ子を補充する（遅延）ので、補充は常にバッファ満杯まで行う
+
+use crate::binary_insertion_sort;
+use crate::trace::NoopObserver;
+
+/// これ以下の長さは二分挿入ソートで済ませる
+const BASE_LEN: usize = 64;
+/// バッファの大きさにかける定数
+const BUFFER_FACTOR: usize = 4;
+
+/// k-ファネル
+/// - 節点は 1 を根とするヒープ順の番号で、葉 k..2k が入力の区間に対応する
+struct Funnel<'a> {
+    inputs: Vec<&'a [i32]>,
+    k: usize,
+    arena: Vec<i32>,
+    /// 節点ごとのバッファの開始位置・容量・読み出し位置・格納数
+    start: Vec<usize>,
+    cap: Vec<usize>,
+    head: Vec<usize>,
+    len: Vec<usize>,
+    /// これ以上補充できない（下の入力を使い切った）
+    exhausted: Vec<bool>,
+}
+
+/// 高さ `height` の完全二分木を van Emde Boas 順に並べる
+/// - 上半分の木を並べた後、その葉の下にぶら下がる木を左から順に並べる
+fn veb_order(root: usize, height: u32, order: &mut Vec<usize>) {
+    if height == 1 {
+        order.push(root);
+        return;
+    }
+    let top = height / 2;
+    let bottom = height - top;
+    veb_order(root, top, order);
+    for i in 0..(1 << top) {
+        veb_order((root << top) + i, bottom, order);
+    }
+}
+
+impl<'a> Funnel<'a> {
+    fn new(mut inputs: Vec<&'a [i32]>) -> Self {
+        // 葉の数を2の冪にそろえ、足りない分は空の入力にする
+        let k = inputs.len().next_power_of_two().max(2);
+        inputs.resize(k, &[]);
+        let levels = k.trailing_zeros();
+        let nodes = 2 * k;
+        let mut funnel = Self {
+            inputs,
+            k,
+            arena: Vec::new(),
+            start: vec![0; nodes],
+            cap: vec![0; nodes],
+            head: vec![0; nodes],
+            len: vec![0; nodes],
+            exhausted: vec![false; nodes],
+        };
+
+        // 内部節点にバッファを van Emde Boas 順で割り当てる
+        let mut order = Vec::with_capacity(k);
+        veb_order(1, levels, &mut order);
+        let mut total = 0;
+        for &v in &order {
+            // 節点の高さ = 葉までの段数
+            let height = levels - (usize::BITS - 1 - v.leading_zeros());
+            let leaves = 1usize << height;
+            let cap = BUFFER_FACTOR * ((leaves as f64).powf(1.5).ceil() as usize);
+            funnel.start[v] = total;
+            funnel.cap[v] = cap;
+            total += cap;
+        }
+        funnel.arena = vec![0; total];
+
+        // 葉は入力をそのまま読む（補充はしない）
+        for leaf in k..nodes {
+            funnel.len[leaf] = funnel.inputs[leaf - k].len();
+            funnel.exhausted[leaf] = true;
+        }
+        funnel
+    }
+
+    fn is_drained(&self, v: usize) -> bool {
+        self.head[v] == self.len[v] && self.exhausted[v]
+    }
+
+    /// 節点 `v` のバッファを満杯になるか入力が尽きるまで補充する
+    fn fill(&mut self, v: usize) {
+        let (left, right) = (2 * v, 2 * v + 1);
+        self.head[v] = 0;
+        self.len[v] = 0;
+        while self.len[v] < self.cap[v] {
+            // 空になった子だけを補充する
+            for child in [left, right] {
+                if self.head[child] == self.len[child] && !self.exhausted[child] {
+                    self.fill(child);
+                }
+            }
+            if self.is_drained(left) && self.is_drained(right) {
+                self.exhausted[v] = true;
+                break;
+            }
+            self.merge_step(v);
+        }
+    }
+
+    /// 子のバッファに今ある分だけを `v` のバッファへマージする
+    /// - どちらかの子が空になるか、`v` のバッファが満杯になったら戻る
+    /// - 片方の子が使い切られていれば、もう片方をまとめてコピーする
+    fn merge_step(&mut self, v: usize) {
+        let (left, right) = (2 * v, 2 * v + 1);
+        let Funnel {
+            inputs,
+            k,
+            arena,
+            start,
+            cap,
+            head,
+            len,
+            exhausted,
+        } = self;
+
+        // 書き込み先の v のバッファと、読み出し元の子のバッファを分けて借りる
+        let (before, rest) = arena.split_at_mut(start[v]);
+        let (dst, after) = rest.split_at_mut(cap[v]);
+        let view = |c: usize| -> &[i32] {
+            if c >= *k {
+                &inputs[c - *k][head[c]..len[c]]
+            } else if start[c] < start[v] {
+                &before[start[c] + head[c]..start[c] + len[c]]
+            } else {
+                let offset = start[c] - start[v] - cap[v];
+                &after[offset + head[c]..offset + len[c]]
+            }
+        };
+        let (a, b) = (view(left), view(right));
+
+        let mut out = len[v];
+        let (mut i, mut j) = (0, 0);
+        while out < dst.len() && i < a.len() && j < b.len() {
+            // 同値は左を優先して安定に
+            if a[i] <= b[j] {
+                dst[out] = a[i];
+                i += 1;
+            } else {
+                dst[out] = b[j];
+                j += 1;
+            }
+            out += 1;
+        }
+        if j == b.len() && exhausted[right] {
+            let m = (a.len() - i).min(dst.len() - out);
+            dst[out..out + m].copy_from_slice(&a[i..i + m]);
+            i += m;
+            out += m;
+        } else if i == a.len() && exhausted[left] {
+            let m = (b.len() - j).min(dst.len() - out);
+            dst[out..out + m].copy_from_slice(&b[j..j + m]);
+            j += m;
+            out += m;
+        }
+
+        head[left] += i;
+        head[right] += j;
+        len[v] = out;
+    }
+
+    /// 全入力をマージして `out` に書く
+    fn merge_into(mut self, out: &mut [i32]) {
+        let mut written = 0;
+        while written < out.len() {
+            self.fill(1);
+            let (start, len) = (self.start[1], self.len[1]);
+            assert!(len > 0, "出力の長さは入力の合計と一致する");
+            out[written..written + len].copy_from_slice(&self.arena[start..start + len]);
+            written += len;
+        }
+    }
+}
+
+fn funnelsort_rec(arr: &mut [i32], scratch: &mut [i32]) {
+    let n = arr.len();
+    if n <= BASE_LEN {
+        binary_insertion_sort(arr, 0, &mut NoopObserver);
+        return;
+    }
+
+    // n^(1/3) 個の区間に分け、それぞれを再帰的にソートする
+    let k = (n as f64).cbrt().ceil() as usize;
+    let segment_len = n.div_ceil(k);
+    for (segment, segment_scratch) in arr
+        .chunks_mut(segment_len)
+        .zip(scratch.chunks_mut(segment_len))
+    {
+        funnelsort_rec(segment, segment_scratch);
+    }
+
+    // k-ファネルでまとめてマージし、元の配列に戻す
+    Funnel::new(arr.chunks(segment_len).collect()).merge_into(scratch);
+    arr.copy_from_slice(scratch);
+}
+
+/// 遅延ファネルソート（安定ソート）
+pub fn funnelsort(arr: &mut [i32]) {
+    let mut scratch = vec![0; arr.len()];
+    funnelsort_rec(arr, &mut scratch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_veb_order() {
+        // 高さ 3: 根 → 左の木 (2, 4, 5) → 右の木 (3, 6, 7)
+        let mut order = Vec::new();
+        veb_order(1, 3, &mut order);
+        assert_eq!(order, vec![1, 2, 4, 5, 3, 6, 7]);
+
+        // 高さ 4: 上の木 (1, 2, 3) の下に高さ 2 の木が4つ
+        let mut order = Vec::new();
+        veb_order(1, 4, &mut order);
+        assert_eq!(
+            order,
+            vec![1, 2, 3, 4, 8, 9, 5, 10, 11, 6, 12, 13, 7, 14, 15]
+        );
+    }
+
+    #[test]
+    fn test_funnelsort() {
+        let mut rng = rand::thread_rng();
+        for n in [0, 1, 2, 63, 64, 65, 1000, 4097, 100_000] {
+            let original: Vec<i32> = (0..n).map(|_| rng.gen_range(0..1000)).collect();
+            let mut expected = original.clone();
+            expected.sort();
+            let mut numbers = original;
+            funnelsort(&mut numbers);
+            assert_eq!(numbers, expected);
+        }
+    }
+
+    #[test]
+    fn test_funnel_with_uneven_inputs() {
+        let inputs: Vec<&[i32]> = vec![&[1, 5, 9], &[], &[2, 2, 2, 2, 2, 2, 2], &[0]];
+        let mut out = vec![0; 11];
+        Funnel::new(inputs).merge_into(&mut out);
+        assert_eq!(out, vec![0, 1, 2, 2, 2, 2, 2, 2, 2, 5, 9]);
+    }
+}
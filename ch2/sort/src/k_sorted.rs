@@ -0,0 +1,104 @@
+//! ほぼ整列済み（k-sorted）の配列のソート
+//! - 各要素が最終位置から高々 k 個しか離れていない配列を O(n log k) でソートする
+//! - 大きさ k+1 の最小ヒープを窓として滑らせ、先頭から確定させていく
+
+use rand::seq::SliceRandom;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// k-sorted な配列のソート（安定ソート）
+/// - ヒープには (値, 元の位置) を入れ、同値は元の位置が小さい方を先に出す
+/// - 入力が k-sorted でない場合、結果は整列しない（`detect_k` で確かめられる）
+pub fn sort_k_sorted(arr: &mut [i32], k: usize) {
+    let n = arr.len();
+    let window = k.saturating_add(1).min(n);
+    let mut heap: BinaryHeap<Reverse<(i32, usize)>> =
+        (0..window).map(|i| Reverse((arr[i], i))).collect();
+
+    // 位置 out の最終的な値は arr[..=out + k] のどこかにあり、それらは全てヒープに入っている
+    for out in 0..n {
+        let Reverse((x, _)) = heap.pop().expect("窓には常に要素がある");
+        arr[out] = x;
+        // 書き込んだのは out までなので、out + k + 1 はまだ元の値のまま
+        let next = out + window;
+        if next < n {
+            heap.push(Reverse((arr[next], next)));
+        }
+    }
+}
+
+/// 配列が k-sorted となる最小の k を求める
+/// - 安定ソート後の位置と元の位置の差の最大値
+pub fn detect_k(arr: &[i32]) -> usize {
+    let mut order: Vec<usize> = (0..arr.len()).collect();
+    order.sort_by_key(|&i| arr[i]);
+    order
+        .iter()
+        .enumerate()
+        .map(|(pos, &i)| pos.abs_diff(i))
+        .max()
+        .unwrap_or(0)
+}
+
+/// 0..n を k+1 個ずつの塊ごとにシャッフルして k-sorted な配列を作る
+pub fn k_sorted_input(n: usize, k: usize) -> Vec<i32> {
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<i32> = (0..n as i32).collect();
+    for block in arr.chunks_mut(k + 1) {
+        block.shuffle(&mut rng);
+    }
+    arr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_sort_k_sorted() {
+        for (n, k) in [
+            (0, 3),
+            (1, 0),
+            (10, 0),
+            (10, 20),
+            (1000, 1),
+            (1000, 7),
+            (5000, 64),
+        ] {
+            let mut arr = k_sorted_input(n, k);
+            assert!(detect_k(&arr) <= k);
+            sort_k_sorted(&mut arr, k);
+            assert_eq!(arr, (0..n as i32).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_sort_k_sorted_with_duplicates() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            // 重複の多い値を整列させてから少しだけ乱す
+            let mut arr: Vec<i32> = (0..200).map(|_| rng.gen_range(0..10)).collect();
+            arr.sort();
+            for _ in 0..20 {
+                let i = rng.gen_range(0..arr.len() - 3);
+                arr.swap(i, i + rng.gen_range(1..4));
+            }
+            let k = detect_k(&arr);
+            let mut expected = arr.clone();
+            expected.sort();
+            sort_k_sorted(&mut arr, k);
+            assert_eq!(arr, expected);
+        }
+    }
+
+    #[test]
+    fn test_detect_k() {
+        assert_eq!(detect_k(&[]), 0);
+        assert_eq!(detect_k(&[1, 2, 3]), 0);
+        assert_eq!(detect_k(&[2, 1, 3]), 1);
+        assert_eq!(detect_k(&[3, 1, 2]), 2);
+        // 同値は安定ソート後の位置で測る
+        assert_eq!(detect_k(&[1, 1, 0]), 2);
+    }
+}
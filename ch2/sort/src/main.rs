@@ -1,5 +1,24 @@
+mod alloc_counter;
+mod analysis;
+mod baseline;
+mod cache_bench;
+mod funnelsort;
+mod k_sorted;
+mod resident;
+mod samplesort;
+mod trace;
+
+#[global_allocator]
+static GLOBAL: alloc_counter::CountingAllocator = alloc_counter::CountingAllocator;
+
+use alloc_counter::AllocStats;
+use analysis::Model;
+use baseline::{Baseline, Record};
 use rand::Rng;
-use std::time::Instant;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+use trace::{NoopObserver, Slot, SortEvent, SortObserver};
 
 // fn merge_with_buf(arr: &mut [i32], mid: usize, buf: &mut [i32]) {
 //     let mut left_index = 0;
@@ -45,7 +64,8 @@ use std::time::Instant;
 
 /// 二分挿入ソート（安定ソート）
 /// - 同じ値があった場合は「先に来たほう」が左に残るようにする
-fn binary_insertion_sort(arr: &mut [i32]) {
+/// - `offset` は `arr` の先頭が元の配列の何番目にあたるか（`obs` に渡す添字に使う）
+fn binary_insertion_sort<O: SortObserver>(arr: &mut [i32], offset: usize, obs: &mut O) {
     for i in 1..arr.len() {
         let x = arr[i];
         let mut left = 0;
@@ -54,6 +74,7 @@ fn binary_insertion_sort(arr: &mut [i32]) {
         // 同値の場合は「右を縮めずに左を広げる」ことで、先に来た方を左に残す
         while left < right {
             let mid = (left + right) / 2;
+            obs.on_event(SortEvent::Compare(offset + mid, offset + i));
             if arr[mid] <= x {
                 // xがarr[mid]以上の場合はmid+1以降に挿入
                 left = mid + 1;
@@ -63,11 +84,22 @@ fn binary_insertion_sort(arr: &mut [i32]) {
             }
         }
 
+        // 移動が不要なら x を退避する必要もない
+        if left == i {
+            continue;
+        }
+        obs.on_event(SortEvent::Move(Slot::Arr(offset + i), Slot::Tmp));
+
         // [left..i) を後ろにシフトして、arr[left] に x を挿入
         for j in (left..i).rev() {
             arr[j + 1] = arr[j];
+            obs.on_event(SortEvent::Move(
+                Slot::Arr(offset + j),
+                Slot::Arr(offset + j + 1),
+            ));
         }
         arr[left] = x;
+        obs.on_event(SortEvent::Move(Slot::Tmp, Slot::Arr(offset + left)));
     }
 }
 
@@ -75,17 +107,36 @@ fn binary_insertion_sort(arr: &mut [i32]) {
 /// - `mid` は `arr` の先頭から数えて左配列の長さ
 /// - 結果は `arr` に上書き
 /// - 同値の場合は「左側を先に採用」して安定性を保つ
-fn stable_merge_with_buf(arr: &mut [i32], mid: usize, buf: &mut [i32]) {
+/// - `offset` は `arr` と `buf` の先頭が元の配列の何番目にあたるか
+fn stable_merge_with_buf<O: SortObserver>(
+    arr: &mut [i32],
+    mid: usize,
+    buf: &mut [i32],
+    offset: usize,
+    obs: &mut O,
+) {
     let mut left_index = 0;
     let mut right_index = mid;
     let mut buf_idx = 0;
 
     while left_index < mid && right_index < arr.len() {
+        obs.on_event(SortEvent::Compare(
+            offset + left_index,
+            offset + right_index,
+        ));
         if arr[left_index] <= arr[right_index] {
             buf[buf_idx] = arr[left_index];
+            obs.on_event(SortEvent::Move(
+                Slot::Arr(offset + left_index),
+                Slot::Buf(offset + buf_idx),
+            ));
             left_index += 1;
         } else {
             buf[buf_idx] = arr[right_index];
+            obs.on_event(SortEvent::Move(
+                Slot::Arr(offset + right_index),
+                Slot::Buf(offset + buf_idx),
+            ));
             right_index += 1;
         }
         buf_idx += 1;
@@ -94,23 +145,50 @@ fn stable_merge_with_buf(arr: &mut [i32], mid: usize, buf: &mut [i32]) {
     // 左側の残りをコピー
     if left_index < mid {
         buf[buf_idx..(buf_idx + (mid - left_index))].copy_from_slice(&arr[left_index..mid]);
+        for k in 0..(mid - left_index) {
+            obs.on_event(SortEvent::Move(
+                Slot::Arr(offset + left_index + k),
+                Slot::Buf(offset + buf_idx + k),
+            ));
+        }
         buf_idx += mid - left_index;
     }
 
     // 右側の残りをコピー
     if right_index < arr.len() {
         buf[buf_idx..(buf_idx + (arr.len() - right_index))].copy_from_slice(&arr[right_index..]);
+        for k in 0..(arr.len() - right_index) {
+            obs.on_event(SortEvent::Move(
+                Slot::Arr(offset + right_index + k),
+                Slot::Buf(offset + buf_idx + k),
+            ));
+        }
         buf_idx += arr.len() - right_index;
     }
 
     // バッファから元の配列に反映
     arr.copy_from_slice(&buf[..buf_idx]);
+    for k in 0..buf_idx {
+        obs.on_event(SortEvent::Move(
+            Slot::Buf(offset + k),
+            Slot::Arr(offset + k),
+        ));
+    }
 }
 
 /// ハイブリッド・ボトムアップ・マージソート（安定ソート）
 /// - 小さな区間（threshold以下）では二分挿入ソート
 /// - それ以上はボトムアップでマージソート
 fn hybrid_bottom_up_merge_sort(arr: &mut [i32], threshold: usize) {
+    hybrid_bottom_up_merge_sort_observed(arr, threshold, &mut NoopObserver);
+}
+
+/// 観測者付きのハイブリッド・ボトムアップ・マージソート
+fn hybrid_bottom_up_merge_sort_observed<O: SortObserver>(
+    arr: &mut [i32],
+    threshold: usize,
+    obs: &mut O,
+) {
     let n = arr.len();
     if n <= 1 {
         return;
@@ -120,7 +198,8 @@ fn hybrid_bottom_up_merge_sort(arr: &mut [i32], threshold: usize) {
     let mut start = 0;
     while start < n {
         let end = (start + threshold).min(n);
-        binary_insertion_sort(&mut arr[start..end]);
+        binary_insertion_sort(&mut arr[start..end], start, obs);
+        obs.on_event(SortEvent::BlockSorted(start, end));
         start += threshold;
     }
 
@@ -132,6 +211,7 @@ fn hybrid_bottom_up_merge_sort(arr: &mut [i32], threshold: usize) {
     //    以後、width を倍々に増やしながら隣接区間をマージする
     let mut width = threshold;
     while width < n {
+        obs.on_event(SortEvent::MergeLevel(width));
         let mut i = 0;
         while i < n {
             let left = i;
@@ -141,7 +221,114 @@ fn hybrid_bottom_up_merge_sort(arr: &mut [i32], threshold: usize) {
             // [left..mid], [mid..right] をマージ
             if mid < right {
                 // マージ先のスライスを arr[left..right] とする
-                stable_merge_with_buf(&mut arr[left..right], mid - left, &mut buf[left..right]);
+                stable_merge_with_buf(
+                    &mut arr[left..right],
+                    mid - left,
+                    &mut buf[left..right],
+                    left,
+                    obs,
+                );
+            }
+            i += 2 * width;
+        }
+        width *= 2;
+    }
+}
+
+/// 回転を使ったインプレース安定マージ（SymMerge）
+/// - `mid` は `arr` の先頭から数えて左配列の長さ
+/// - 追加のバッファを使わない代わりに、移動回数は O(n log n) になる
+fn sym_merge(arr: &mut [i32], mid: usize) {
+    let n = arr.len();
+    if mid == 0 || mid == n {
+        return;
+    }
+
+    // 左が1要素なら、右側で挿入位置を二分探索して回転する
+    if mid == 1 {
+        let mut left = 1;
+        let mut right = n;
+        while left < right {
+            let h = (left + right) / 2;
+            if arr[h] < arr[0] {
+                left = h + 1;
+            } else {
+                right = h;
+            }
+        }
+        arr[..left].rotate_left(1);
+        return;
+    }
+
+    // 右が1要素なら、左側で挿入位置を二分探索して回転する（同値は左を先に残す）
+    if n - mid == 1 {
+        let mut left = 0;
+        let mut right = mid;
+        while left < right {
+            let h = (left + right) / 2;
+            if arr[h] <= arr[mid] {
+                left = h + 1;
+            } else {
+                right = h;
+            }
+        }
+        arr[left..].rotate_right(1);
+        return;
+    }
+
+    // 中央 half を軸に対称な位置で境界を二分探索し、
+    // [start..mid) と [mid..end) を回転してから左右を再帰的にマージする
+    let half = n / 2;
+    let sum = half + mid;
+    let (mut start, mut r) = if mid > half {
+        (sum - n, half)
+    } else {
+        (0, mid)
+    };
+    let p = sum - 1;
+    while start < r {
+        let c = (start + r) / 2;
+        if arr[p - c] >= arr[c] {
+            start = c + 1;
+        } else {
+            r = c;
+        }
+    }
+    let end = sum - start;
+    if start < mid && mid < end {
+        arr[start..end].rotate_left(mid - start);
+    }
+    if start < half {
+        sym_merge(&mut arr[..half], start);
+    }
+    if end < n {
+        sym_merge(&mut arr[half..], end - half);
+    }
+}
+
+/// インプレース版のハイブリッド・ボトムアップ・マージソート（安定ソート）
+/// - マージ用バッファを確保せず、`sym_merge` で隣接区間をマージする
+fn hybrid_bottom_up_merge_sort_in_place(arr: &mut [i32], threshold: usize) {
+    let n = arr.len();
+    if n <= 1 {
+        return;
+    }
+
+    let mut start = 0;
+    while start < n {
+        let end = (start + threshold).min(n);
+        binary_insertion_sort(&mut arr[start..end], start, &mut NoopObserver);
+        start += threshold;
+    }
+
+    let mut width = threshold;
+    while width < n {
+        let mut i = 0;
+        while i < n {
+            let mid = (i + width).min(n);
+            let right = (i + 2 * width).min(n);
+            if mid < right {
+                sym_merge(&mut arr[i..right], mid - i);
             }
             i += 2 * width;
         }
@@ -158,48 +345,517 @@ fn is_sorted(arr: &[i32]) -> bool {
     true
 }
 
+/// トレース用の入力を作り、ソートしながらイベントを記録する
+fn record_trace(n: usize, threshold: usize) -> (Vec<i32>, Vec<SortEvent>) {
+    let mut rng = rand::thread_rng();
+    let initial: Vec<i32> = (0..n).map(|_| rng.gen_range(0..100)).collect();
+    let mut arr = initial.clone();
+    let mut events = Vec::new();
+    hybrid_bottom_up_merge_sort_observed(&mut arr, threshold, &mut events);
+    assert!(is_sorted(&arr));
+    (initial, events)
+}
+
+/// `trace [n] [threshold] [frame_ms]`: 端末でコマ送りアニメーションを再生する
+/// `svg [n] [threshold] [frame_ms]`: SVG アニメーションを標準出力に書き出す
+fn trace_demo(mode: &str, args: &[String]) {
+    let usage = |message: String| -> ! {
+        eprintln!("{}", message);
+        eprintln!("使い方: {} [n] [threshold] [frame_ms]", mode);
+        std::process::exit(2);
+    };
+    let arg = |i: usize, default: usize| -> usize {
+        args.get(i).map_or(default, |s| {
+            s.parse()
+                .unwrap_or_else(|_| usage(format!("数値を指定してください: {}", s)))
+        })
+    };
+    let n = arg(0, 16);
+    let threshold = arg(1, 4);
+    let frame_ms = arg(2, if mode == "svg" { 100 } else { 80 });
+    if n == 0 {
+        usage("n は 1 以上を指定してください".to_string());
+    }
+    // threshold = 0 では区間が進まず終わらない
+    if threshold == 0 {
+        usage("threshold は 1 以上を指定してください".to_string());
+    }
+    if args.len() > 3 {
+        usage(format!("余分な引数があります: {}", args[3]));
+    }
+    let (initial, events) = record_trace(n, threshold);
+
+    if mode == "svg" {
+        print!("{}", trace::render_svg(&initial, &events, frame_ms));
+        return;
+    }
+    let frame = Duration::from_millis(frame_ms as u64);
+    for text in trace::render_text_frames(&initial, &events) {
+        // 画面を消してカーソルを左上に戻してから描く
+        print!("\x1b[2J\x1b[H{}", text);
+        std::io::stdout().flush().unwrap();
+        std::thread::sleep(frame);
+    }
+}
+
+/// `[iterations]` だけを取るモードの引数を読む（既定は 3）
+/// - 数でないか 0 なら、使い方を表示して終了コード 2 で終わる
+fn iterations_arg(mode: &str, args: &[String]) -> usize {
+    let usage = |message: String| -> ! {
+        eprintln!("{}", message);
+        eprintln!("使い方: {} [iterations]", mode);
+        std::process::exit(2);
+    };
+    if args.len() > 1 {
+        usage(format!("余分な引数があります: {}", args[1]));
+    }
+    let iterations = args.first().map_or(3, |s| {
+        s.parse()
+            .unwrap_or_else(|_| usage(format!("数値を指定してください: {}", s)))
+    });
+    // 0 回では平均が 0/0 になる
+    if iterations == 0 {
+        usage("iterations は 1 以上を指定してください".to_string());
+    }
+    iterations
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some(mode @ ("trace" | "svg")) => trace_demo(mode, &args[1..]),
+        Some("ksorted") => k_sorted_benchmark(iterations_arg("ksorted", &args[1..])),
+        Some("cache") => cache_bench::run(iterations_arg("cache", &args[1..])),
+        _ => {
+            let options = match BenchOptions::parse(&args) {
+                Ok(options) => options,
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                }
+            };
+            if !benchmark(&options) {
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// ベースラインとの比較に必要な反復回数
+/// - 両方 n 回のとき U 検定の p 値は最小でも 1 / C(2n, n) で、n = 3 では 0.05 に届かない
+const MIN_BASELINE_ITERATIONS: usize = 4;
+
+/// `--sizes` の A の下限（これより小さい要素数は計測誤差ばかりで計算量の推定を乱す）
+const MIN_EXPONENT_START: u32 = 6;
+/// `--sizes` の B の上限（要素数 2^(B-1) が i32 に収まる）
+const MAX_EXPONENT_END: u32 = 31;
+
+/// ベンチマークのオプション
+/// - `--iterations N`: 要素数ごとの反復回数
+/// - `--sizes A..B`: 要素数を 2^A から 2^(B-1) まで変える（6 ≤ A < B ≤ 31）
+/// - `--save-baseline PATH`: 結果をベースラインとして保存する（`--iterations` は 4 以上、保存できなければ終了コード 2）
+/// - `--baseline PATH`: 保存済みのベースラインと比較し、劣化があれば終了コード 1 で終わる
+/// - `--threshold R`: 劣化とみなす中央値の変化率（既定は 0.05、0 以上の有限値）
+/// - `--threads N`: 並列サンプルソートの最大スレッド数（既定は論理コア数、上限は 1024）
+struct BenchOptions {
+    iterations: usize,
+    exponents: std::ops::Range<u32>,
+    save_baseline: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+    threads: usize,
+}
+
+impl BenchOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = BenchOptions {
+            iterations: 1,
+            exponents: 25..31,
+            save_baseline: None,
+            baseline: None,
+            threshold: 0.05,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("{} には値が必要です", flag))
+            };
+            match flag.as_str() {
+                "--iterations" => {
+                    options.iterations = value()?
+                        .parse()
+                        .map_err(|e| format!("--iterations: {}", e))?
+                }
+                "--sizes" => {
+                    let range = value()?;
+                    let (from, to) = range.split_once("..").ok_or_else(|| {
+                        format!("--sizes は A..B の形で指定してください: {}", range)
+                    })?;
+                    let from = from.parse().map_err(|e| format!("--sizes: {}", e))?;
+                    let to = to.parse().map_err(|e| format!("--sizes: {}", e))?;
+                    options.exponents = from..to;
+                }
+                "--save-baseline" => options.save_baseline = Some(value()?.clone()),
+                "--baseline" => options.baseline = Some(value()?.clone()),
+                "--threshold" => {
+                    options.threshold = value()?
+                        .parse()
+                        .map_err(|e| format!("--threshold: {}", e))?
+                }
+                "--threads" => {
+                    options.threads = value()?.parse().map_err(|e| format!("--threads: {}", e))?
+                }
+                _ => return Err(format!("不明なオプションです: {}", flag)),
+            }
+        }
+        if options.threads == 0 || options.threads > samplesort::MAX_THREADS {
+            return Err(format!(
+                "--threads は 1 以上 {} 以下を指定してください",
+                samplesort::MAX_THREADS
+            ));
+        }
+        if !options.threshold.is_finite() || options.threshold < 0.0 {
+            return Err(format!(
+                "--threshold は 0 以上の有限の値を指定してください: {}",
+                options.threshold
+            ));
+        }
+        if options.iterations == 0 {
+            return Err("--iterations は 1 以上を指定してください".to_string());
+        }
+        if (options.baseline.is_some() || options.save_baseline.is_some())
+            && options.iterations < MIN_BASELINE_ITERATIONS
+        {
+            return Err(format!(
+                "ベースラインを使うには --iterations を {} 以上にしてください（少ないと劣化を検出できません）",
+                MIN_BASELINE_ITERATIONS
+            ));
+        }
+        if options.exponents.is_empty()
+            || options.exponents.start < MIN_EXPONENT_START
+            || options.exponents.end > MAX_EXPONENT_END
+        {
+            return Err(format!(
+                "--sizes は {} ≤ A < B ≤ {} の範囲で指定してください: {}..{}",
+                MIN_EXPONENT_START,
+                MAX_EXPONENT_END,
+                options.exponents.start,
+                options.exponents.end
+            ));
+        }
+        Ok(options)
+    }
+}
+
+/// 1回分の計測結果
+struct Measurement {
+    millis: f64,
+    alloc: AllocStats,
+    /// ソート中のプロセスの最大常駐量（測れなければ None）
+    peak_resident: Option<usize>,
+}
+
+/// `original` の複製を `sort` で整列し、時間とメモリを計測する
+/// - 複製そのものの時間と確保は計測に含めない
+fn measure(original: &[i32], sort: impl Fn(&mut [i32])) -> Measurement {
+    let mut numbers = original.to_vec();
+    alloc_counter::reset();
+    resident::reset_peak();
+    let start = Instant::now();
+    sort(&mut numbers);
+    let millis = start.elapsed().as_secs_f64() * 1000.0;
+    let alloc = alloc_counter::stats();
+    let peak_resident = resident::peak_bytes();
+    assert!(is_sorted(&numbers));
+    Measurement {
+        millis,
+        alloc,
+        peak_resident,
+    }
+}
+
+type SortFn = fn(&mut [i32]);
+
+/// ベンチマーク対象のソート（名前, ソート関数, 想定する計算量）
+const ALGORITHMS: [(&str, SortFn, Model); 5] = [
+    (
+        "改良後安定ハイブリッドソート",
+        |a| hybrid_bottom_up_merge_sort(a, 16),
+        Model::NLogN,
+    ),
+    (
+        "インプレース版ハイブリッドソート",
+        |a| hybrid_bottom_up_merge_sort_in_place(a, 16),
+        Model::NLogN,
+    ),
+    ("ファネルソート", funnelsort::funnelsort, Model::NLogN),
+    ("標準ソート", |a| a.sort(), Model::NLogN),
+    ("不安定ソート", |a| a.sort_unstable(), Model::NLogN),
+];
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+/// 要素数ごとの平均時間から計算量を推定し、想定と違うものに印をつける
+fn report_complexity(lengths: &[usize], samples: &[Vec<Vec<f64>>]) {
+    println!("計算量の推定:");
+    for ((name, _, expected), per_length) in ALGORITHMS.iter().zip(samples) {
+        let points: Vec<(f64, f64)> = lengths
+            .iter()
+            .zip(per_length)
+            .map(|(&n, millis)| (n as f64, mean(millis)))
+            .collect();
+        let Some(fit) = analysis::fit(&points) else {
+            println!("  {}: 計測点が足りないため推定できません", name);
+            continue;
+        };
+        println!(
+            "  {}: 指数 {:.2} (R² {:.3}), 最適モデル {} (R² {:.3}){}",
+            name,
+            fit.exponent,
+            fit.exponent_r_squared,
+            fit.best.name(),
+            fit.best_r_squared,
+            if fit.best_r_squared < analysis::MIN_R_SQUARED {
+                " ※当てはまりが悪いため判定しません".to_string()
+            } else if fit.best == *expected {
+                String::new()
+            } else {
+                format!(" ※想定 {} から外れています", expected.name())
+            }
+        );
+    }
+}
+
+/// 並列サンプルソートのスレッド数を 1, 2, 4, ... と `max_threads` まで変えて計測する
+/// - 1スレッドは逐次のハイブリッドソートと同じなので、速度向上はそれに対する倍率
+/// - `max_threads` は入力の長さと論理コア数に応じて実際に使う数まで減らす
+fn report_scaling(original: &[i32], iterations: usize, max_threads: usize) {
+    let max_threads = samplesort::effective_threads(original.len(), max_threads).max(1);
+    let mut counts: Vec<usize> = std::iter::successors(Some(1), |&t| Some(t * 2))
+        .take_while(|&t| t < max_threads)
+        .collect();
+    counts.push(max_threads);
+
+    let mut base = None;
+    let results: Vec<String> = counts
+        .iter()
+        .map(|&threads| {
+            let millis: Vec<f64> = (0..iterations)
+                .map(|_| measure(original, |a| samplesort::samplesort(a, threads)).millis)
+                .collect();
+            let millis = mean(&millis);
+            let base = *base.get_or_insert(millis);
+            format!(
+                "{}スレッド {:.0}ms ({:.2}倍)",
+                threads,
+                millis,
+                base / millis
+            )
+        })
+        .collect();
+    println!("  並列サンプルソート: {}", results.join(", "));
+}
+
+/// `ksorted [iterations]`: ほぼ整列済みの入力で、窓ヒープとハイブリッドソートを比べる
+fn k_sorted_benchmark(iterations: usize) {
+    let n = 1 << 24;
+    for k in [1, 8, 64, 512, 4096] {
+        let original = k_sorted::k_sorted_input(n, k);
+        let detected = k_sorted::detect_k(&original);
+        let window: Vec<f64> = (0..iterations)
+            .map(|_| measure(&original, |a| k_sorted::sort_k_sorted(a, detected)).millis)
+            .collect();
+        let hybrid: Vec<f64> = (0..iterations)
+            .map(|_| measure(&original, |a| hybrid_bottom_up_merge_sort(a, 16)).millis)
+            .collect();
+        println!(
+            "リストの長さ: {}, k: {}, k-sorted ソート時間: {:.0}ms, 改良後安定ハイブリッドソート時間: {:.0}ms",
+            n,
+            detected,
+            mean(&window),
+            mean(&hybrid)
+        );
+    }
+}
+
+/// ベースラインと比べて結果を表示する。劣化が無ければ `true`
+fn report_comparison(baseline: &Baseline, current: &Baseline, threshold: f64) -> bool {
+    println!("ベースラインとの比較:");
+    let comparisons = baseline::compare(baseline, current, threshold, 0.05);
+    for c in &comparisons {
+        println!(
+            "  {} (長さ {}): {:+.1}%, p = {:.3}{}",
+            c.algorithm,
+            c.length,
+            c.relative_change * 100.0,
+            c.p_value,
+            if c.regressed { " ※劣化" } else { "" }
+        );
+    }
+    !comparisons.iter().any(|c| c.regressed)
+}
+
+/// ベンチマークを実行する。ベースラインとの比較で劣化が見つかったら `false`
+fn benchmark(options: &BenchOptions) -> bool {
     let mut rng = rand::thread_rng();
-    let iterations = 1;
+    let iterations = options.iterations;
+
+    // 比較対象は先に読み込み、壊れていたら計測前に止める
+    let previous = options.baseline.as_ref().map(|path| {
+        let baseline = Baseline::load(path).unwrap_or_else(|e| {
+            eprintln!("ベースライン {} を読めません: {}", path, e);
+            std::process::exit(2);
+        });
+        if let Some(record) = baseline
+            .records
+            .iter()
+            .find(|r| r.millis.len() < MIN_BASELINE_ITERATIONS)
+        {
+            eprintln!(
+                "ベースライン {} の {} (長さ {}) は {} 回分しかなく、比較できません（{} 回以上必要です）",
+                path,
+                record.algorithm,
+                record.length,
+                record.millis.len(),
+                MIN_BASELINE_ITERATIONS
+            );
+            std::process::exit(2);
+        }
+        baseline
+    });
+
+    let mut lengths = Vec::new();
+    // アルゴリズムごと・要素数ごとの反復ごとの時間
+    let mut samples: Vec<Vec<Vec<f64>>> = vec![Vec::new(); ALGORITHMS.len()];
 
     // マージソートとハイブリッドソートを比較
-    for i in 25..31 {
-        let mut mysort_duration = 0;
-        let mut standard_duration = 0;
-        let mut unstable_duration = 0;
+    for i in options.exponents.clone() {
         let list_length = 2_i32.pow(i);
+        let mut millis: Vec<Vec<f64>> = vec![Vec::with_capacity(iterations); ALGORITHMS.len()];
+        let mut allocs: Vec<AllocStats> = vec![AllocStats::default(); ALGORITHMS.len()];
+        let mut resident: Vec<Option<usize>> = vec![None; ALGORITHMS.len()];
         for _ in 0..iterations {
-            let original_numbers: Vec<i32> =
-                (0..list_length).map(|_| rng.gen_range(0..1000000)).collect();
-
-            let start = Instant::now();
-            let mut numbers = original_numbers.clone();
-            hybrid_bottom_up_merge_sort(&mut numbers, 16);
-            let duration = start.elapsed();
-            mysort_duration += duration.as_millis();
-            assert!(is_sorted(&numbers));
-
-            let start = Instant::now();
-            let mut numbers = original_numbers.clone();
-            // 標準機能のソート
-            numbers.sort();
-            let duration = start.elapsed();
-            standard_duration += duration.as_millis();
-
-            // 標準の不安定ソート
-            let start = Instant::now();
-            let mut numbers = original_numbers.clone();
-            numbers.sort_unstable();
-            let duration = start.elapsed();
-            unstable_duration += duration.as_millis();
+            let original_numbers: Vec<i32> = (0..list_length)
+                .map(|_| rng.gen_range(0..1000000))
+                .collect();
+
+            for (k, (_, sort, _)) in ALGORITHMS.iter().enumerate() {
+                let m = measure(&original_numbers, sort);
+                millis[k].push(m.millis);
+                allocs[k].allocations += m.alloc.allocations;
+                allocs[k].allocated_bytes += m.alloc.allocated_bytes;
+                allocs[k].peak_bytes = allocs[k].peak_bytes.max(m.alloc.peak_bytes);
+                resident[k] = resident[k].max(m.peak_resident);
+            }
         }
-        println!(
-                "リストの長さ: {}, 改良後安定ハイブリッドソート時間: {}ms, 標準ソート時間: {}ms, 不安定ソート時間: {}ms",
-                list_length,
-                mysort_duration / iterations,
-                standard_duration / iterations,
-                unstable_duration / iterations
+        println!("リストの長さ: {}", list_length);
+        lengths.push(list_length as usize);
+        for (k, (name, _, _)) in ALGORITHMS.iter().enumerate() {
+            let resident = resident[k].map_or("不明".to_string(), |b| format!("{} bytes", b));
+            println!(
+                "  {}: {:.0}ms, 確保回数: {}, 確保量: {} bytes, ヒープ増分のピーク: {} bytes, 最大常駐量: {}",
+                name,
+                mean(&millis[k]),
+                allocs[k].allocations / iterations,
+                allocs[k].allocated_bytes / iterations,
+                allocs[k].peak_bytes,
+                resident
             );
+        }
+        for (k, m) in millis.into_iter().enumerate() {
+            samples[k].push(m);
+        }
+
+        let original_numbers: Vec<i32> = (0..list_length)
+            .map(|_| rng.gen_range(0..1000000))
+            .collect();
+        report_scaling(&original_numbers, iterations, options.threads);
+    }
+
+    report_complexity(&lengths, &samples);
+
+    let current = Baseline {
+        records: ALGORITHMS
+            .iter()
+            .zip(&samples)
+            .flat_map(|((name, _, _), per_length)| {
+                lengths
+                    .iter()
+                    .zip(per_length)
+                    .map(|(&length, millis)| Record {
+                        algorithm: name.to_string(),
+                        length,
+                        millis: millis.clone(),
+                    })
+            })
+            .collect(),
+    };
+    let saved = options
+        .save_baseline
+        .as_ref()
+        .is_none_or(|path| match current.save(path) {
+            Ok(()) => {
+                println!("ベースラインを {} に保存しました", path);
+                true
+            }
+            Err(e) => {
+                eprintln!("ベースライン {} を保存できません: {}", path, e);
+                false
+            }
+        });
+    let passed = match previous {
+        Some(previous) => report_comparison(&previous, &current, options.threshold),
+        None => true,
+    };
+    // 比較結果は表示してから、保存の失敗を終了コードで知らせる
+    if !saved {
+        std::process::exit(2);
+    }
+    passed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hybrid_bottom_up_merge_sort_in_place() {
+        let mut rng = rand::thread_rng();
+        for n in [0, 1, 2, 15, 16, 17, 100, 1000] {
+            let original: Vec<i32> = (0..n).map(|_| rng.gen_range(0..20)).collect();
+            let mut expected = original.clone();
+            expected.sort();
+            let mut numbers = original.clone();
+            hybrid_bottom_up_merge_sort_in_place(&mut numbers, 4);
+            assert_eq!(numbers, expected);
+        }
+    }
+
+    #[test]
+    fn test_bench_options_validation() {
+        let parse = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            BenchOptions::parse(&args)
+        };
+        assert!(parse(&["--sizes", "10..31"]).is_ok());
+        assert!(parse(&["--sizes", "10..32"]).is_err());
+        assert!(parse(&["--sizes", "12..12"]).is_err());
+        assert!(parse(&["--sizes", "0..3"]).is_err());
+        assert!(parse(&["--sizes", "6..9"]).is_ok());
+        assert!(parse(&["--baseline", "b.json"]).is_err());
+        assert!(parse(&["--save-baseline", "b.json", "--iterations", "3"]).is_err());
+        assert!(parse(&["--save-baseline", "b.json", "--iterations", "4"]).is_ok());
+        assert!(parse(&["--threads", "1024"]).is_ok());
+        assert!(parse(&["--threads", "1025"]).is_err());
+        assert!(parse(&["--threads", "0"]).is_err());
+        assert!(parse(&["--threshold", "0"]).is_ok());
+        assert!(parse(&["--threshold", "NaN"]).is_err());
+        assert!(parse(&["--threshold", "inf"]).is_err());
+        assert!(parse(&["--threshold", "-0.1"]).is_err());
     }
 }
 
@@ -0,0 +1,38 @@
+//! プロセスの最大常駐量（Linux の `/proc/self/status` の VmHWM）
+//! - `/proc/self/clear_refs` に 5 を書くと、最大値を現在の常駐量に戻せる
+//! - `/proc` がない環境では測れないので `None` を返す
+
+/// 最大常駐量を現在の常駐量に戻す
+pub fn reset_peak() {
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// 最大常駐量（バイト）
+pub fn peak_bytes() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_hwm(&status)
+}
+
+/// `VmHWM:    1234 kB` の行を読む
+fn parse_vm_hwm(status: &str) -> Option<usize> {
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: usize = line["VmHWM:".len()..]
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vm_hwm() {
+        let status = "Name:\tsort\nVmPeak:\t  9000 kB\nVmHWM:\t    1636 kB\nVmRSS:\t 1500 kB\n";
+        assert_eq!(parse_vm_hwm(status), Some(1636 * 1024));
+        assert_eq!(parse_vm_hwm("Name:\tsort\n"), None);
+    }
+}
@@ -0,0 +1,298 @@
+//! 多コア向けの並列サンプルソート（安定ソート）
+//! - 無作為に多めに標本を取って（オーバーサンプリング）バケットの境界値を決める
+//! - 境界値は Eytzinger 順の完全二分木に並べ、分岐なしで要素のバケットを求める
+//! - 分類・振り分け・バケットごとのソートをそれぞれスレッドで並列に行う
+//! - 同じ境界値が並ぶほど重複が多いときは、境界値と等しい値を等値バケットに分ける
+//!   （等値バケットは整列済みなので、重複の多い入力でもソートが1つのバケットに偏らない）
+//! - 振り分けはチャンク順・元の順を保つので、全体として安定
+
+use crate::hybrid_bottom_up_merge_sort;
+use rand::Rng;
+use std::cmp::Reverse;
+use std::sync::Mutex;
+use std::thread;
+
+/// 境界値1つあたりの標本数
+const OVERSAMPLING: usize = 16;
+/// これ以下の長さは並列化せずハイブリッドソートで済ませる
+const SEQUENTIAL_LEN: usize = 1 << 16;
+/// バケット数の上限（等値バケットを含めた振り分け先の番号を u16 に収める）
+const MAX_BUCKETS: usize = 1 << 12;
+/// 指定できるスレッド数の上限（バケット数 threads * 4 が MAX_BUCKETS に収まる）
+pub const MAX_THREADS: usize = MAX_BUCKETS / 4;
+/// 論理コア1つあたりに起こすスレッド数の上限
+const THREADS_PER_CORE: usize = 2;
+
+/// 境界値の二分木
+/// - `tree[1..buckets]` が Eytzinger 順（ヒープ順）に並んだ buckets - 1 個の境界値
+/// - `upper[b]` はバケット b の上端の境界値（最後のバケットは i32::MAX）
+struct SplitterTree {
+    tree: Vec<i32>,
+    levels: u32,
+    upper: Vec<i32>,
+    /// 等値バケットを使うか（同じ境界値が並ぶとき）
+    equality: bool,
+}
+
+impl SplitterTree {
+    fn new(arr: &[i32], buckets: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let mut sample: Vec<i32> = (0..buckets * OVERSAMPLING)
+            .map(|_| arr[rng.gen_range(0..arr.len())])
+            .collect();
+        sample.sort_unstable();
+        let splitters: Vec<i32> = (1..buckets).map(|i| sample[i * OVERSAMPLING]).collect();
+        Self::from_splitters(&splitters)
+    }
+
+    /// 昇順の境界値（個数は 2の冪 - 1）から木を作る
+    fn from_splitters(splitters: &[i32]) -> Self {
+        fn fill(splitters: &[i32], tree: &mut [i32], node: usize, next: &mut usize) {
+            if node < tree.len() {
+                // 中間順で辿ると昇順になるように置く
+                fill(splitters, tree, 2 * node, next);
+                tree[node] = splitters[*next];
+                *next += 1;
+                fill(splitters, tree, 2 * node + 1, next);
+            }
+        }
+        let buckets = splitters.len() + 1;
+        debug_assert!(buckets.is_power_of_two());
+        let mut tree = vec![0; buckets];
+        fill(splitters, &mut tree, 1, &mut 0);
+        let mut upper = splitters.to_vec();
+        upper.push(i32::MAX);
+        Self {
+            tree,
+            levels: buckets.trailing_zeros(),
+            upper,
+            equality: splitters.windows(2).any(|w| w[0] == w[1]),
+        }
+    }
+
+    /// `x` の入るバケット番号
+    /// - 境界値と等しい値は左のバケットに入る
+    /// - 比較結果を添字の計算に使うので分岐しない
+    #[inline]
+    fn classify(&self, x: i32) -> usize {
+        let mut j = 1;
+        for _ in 0..self.levels {
+            j = 2 * j + (x > self.tree[j]) as usize;
+        }
+        j - self.tree.len()
+    }
+
+    /// 振り分け先の数（等値バケットを使うなら、バケットごとに2つ）
+    fn slots(&self) -> usize {
+        if self.equality {
+            2 * self.tree.len()
+        } else {
+            self.tree.len()
+        }
+    }
+
+    /// `x` の振り分け先
+    /// - 等値バケットを使うときは、バケット b の上端の境界値と等しい値を 2b + 1、残りを 2b に入れる
+    #[inline]
+    fn slot(&self, x: i32) -> usize {
+        let b = self.classify(x);
+        if self.equality {
+            2 * b + (x == self.upper[b]) as usize
+        } else {
+            b
+        }
+    }
+
+    /// 振り分け先が等値バケット（中身がすべて等しく、ソート不要）か
+    fn is_equality_slot(&self, slot: usize) -> bool {
+        self.equality && slot % 2 == 1
+    }
+}
+
+/// `slice` を `lens` の長さの連続する区間に切り分ける
+fn split_by_lens<'a>(mut slice: &'a mut [i32], lens: &[usize]) -> Vec<&'a mut [i32]> {
+    lens.iter()
+        .map(|&len| {
+            let (piece, rest) = std::mem::take(&mut slice).split_at_mut(len);
+            slice = rest;
+            piece
+        })
+        .collect()
+}
+
+/// 実際に使うスレッド数
+/// - チャンクが SEQUENTIAL_LEN より短くならず、論理コア数の数倍を超えないように抑える
+pub fn effective_threads(n: usize, threads: usize) -> usize {
+    let cores = thread::available_parallelism().map_or(1, |c| c.get());
+    threads
+        .min(n / SEQUENTIAL_LEN)
+        .min(cores * THREADS_PER_CORE)
+        .min(MAX_THREADS)
+}
+
+/// 並列サンプルソート
+/// - `threads` は最大スレッド数で、入力の長さと論理コア数に応じて減らす
+/// - 1 スレッドになる場合は、逐次のハイブリッドソートと同じ
+pub fn samplesort(arr: &mut [i32], threads: usize) {
+    let n = arr.len();
+    let threads = effective_threads(n, threads);
+    if threads <= 1 {
+        hybrid_bottom_up_merge_sort(arr, 16);
+        return;
+    }
+
+    // 1. 標本から境界値を決める（バケット数はスレッド数の数倍の2の冪）
+    let buckets = (threads * 4).next_power_of_two().min(MAX_BUCKETS);
+    let tree = SplitterTree::new(arr, buckets);
+    let tree = &tree;
+    let slots = tree.slots();
+
+    // 2. チャンクごとに各要素の振り分け先を求め、振り分け先ごとの個数を数える
+    let chunk_len = n.div_ceil(threads);
+    let mut bucket_of = vec![0u16; n];
+    let counts: Vec<Vec<usize>> = thread::scope(|s| {
+        let handles: Vec<_> = arr
+            .chunks(chunk_len)
+            .zip(bucket_of.chunks_mut(chunk_len))
+            .map(|(chunk, ids)| {
+                s.spawn(move || {
+                    let mut count = vec![0; slots];
+                    for (&x, id) in chunk.iter().zip(ids.iter_mut()) {
+                        let b = tree.slot(x);
+                        *id = b as u16;
+                        count[b] += 1;
+                    }
+                    count
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    // 3. 出力を (振り分け先, チャンク) の順に切り分け、各チャンクが自分の区間へ振り分ける
+    let mut out = vec![0; n];
+    let lens: Vec<usize> = (0..slots)
+        .flat_map(|b| counts.iter().map(move |count| count[b]))
+        .collect();
+    let mut pieces: Vec<Vec<&mut [i32]>> = counts.iter().map(|_| Vec::new()).collect();
+    for (i, piece) in split_by_lens(&mut out, &lens).into_iter().enumerate() {
+        pieces[i % counts.len()].push(piece);
+    }
+    thread::scope(|s| {
+        for ((chunk, ids), mut dst) in arr
+            .chunks(chunk_len)
+            .zip(bucket_of.chunks(chunk_len))
+            .zip(pieces)
+        {
+            s.spawn(move || {
+                let mut pos = vec![0; slots];
+                for (&x, &b) in chunk.iter().zip(ids) {
+                    let b = b as usize;
+                    dst[b][pos[b]] = x;
+                    pos[b] += 1;
+                }
+            });
+        }
+    });
+
+    // 4. 大きいバケットから順に空いたスレッドがソートし、元の配列へ書き戻す
+    //    等値バケットはソートせずに書き戻すだけ
+    let bucket_lens: Vec<usize> = (0..slots)
+        .map(|b| counts.iter().map(|count| count[b]).sum())
+        .collect();
+    let mut work: Vec<(bool, &mut [i32], &mut [i32])> = split_by_lens(&mut out, &bucket_lens)
+        .into_iter()
+        .zip(split_by_lens(arr, &bucket_lens))
+        .enumerate()
+        .map(|(slot, (bucket, dst))| (!tree.is_equality_slot(slot), bucket, dst))
+        .collect();
+    work.sort_by_key(|(needs_sort, bucket, _)| Reverse((*needs_sort, bucket.len())));
+    let queue = Mutex::new(work.into_iter());
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let next = queue.lock().unwrap().next();
+                let Some((needs_sort, bucket, dst)) = next else {
+                    break;
+                };
+                if needs_sort {
+                    hybrid_bottom_up_merge_sort(bucket, 16);
+                }
+                dst.copy_from_slice(bucket);
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let tree = SplitterTree::from_splitters(&[10, 20, 20, 30, 40, 50, 60]);
+        let linear = |x: i32| {
+            [10, 20, 20, 30, 40, 50, 60]
+                .iter()
+                .filter(|&&s| x > s)
+                .count()
+        };
+        for x in 0..70 {
+            assert_eq!(tree.classify(x), linear(x), "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn test_equality_slots() {
+        let tree = SplitterTree::from_splitters(&[10, 20, 20, 30, 40, 50, 60]);
+        assert_eq!(tree.slots(), 16);
+        assert_eq!(tree.slot(5), 0);
+        assert_eq!(tree.slot(10), 1);
+        assert_eq!(tree.slot(15), 2);
+        assert_eq!(tree.slot(20), 3);
+        assert_eq!(tree.slot(25), 6);
+        assert_eq!(tree.slot(i32::MAX), 15);
+        for x in 0..70 {
+            assert_eq!(
+                tree.is_equality_slot(tree.slot(x)),
+                [10, 20, 30, 40, 50, 60].contains(&x)
+            );
+        }
+        // 境界値が重ならなければ等値バケットは作らない
+        let tree = SplitterTree::from_splitters(&[10, 20, 30]);
+        assert_eq!(tree.slots(), 4);
+        assert_eq!(tree.slot(20), 1);
+    }
+
+    #[test]
+    fn test_duplicates_go_to_equality_slots() {
+        // 値が 5 種類しかなければ、どの値も境界値に現れ、すべて等値バケットに入る
+        let mut rng = rand::thread_rng();
+        let arr: Vec<i32> = (0..300_001).map(|_| rng.gen_range(0..5)).collect();
+        let tree = SplitterTree::new(&arr, 32);
+        assert!(arr.iter().all(|&x| tree.is_equality_slot(tree.slot(x))));
+    }
+
+    #[test]
+    fn test_effective_threads() {
+        assert_eq!(effective_threads(SEQUENTIAL_LEN, 8), 1);
+        assert_eq!(effective_threads(3 * SEQUENTIAL_LEN, 1), 1);
+        assert!(effective_threads(3 * SEQUENTIAL_LEN, 8) <= 3);
+        assert!(effective_threads(usize::MAX, usize::MAX) <= MAX_THREADS);
+    }
+
+    #[test]
+    fn test_samplesort() {
+        let mut rng = rand::thread_rng();
+        for (n, max) in [(0, 10), (1000, 10), (200_000, 1_000_000), (300_001, 5)] {
+            let original: Vec<i32> = (0..n).map(|_| rng.gen_range(0..max)).collect();
+            let mut expected = original.clone();
+            expected.sort();
+            for threads in [1, 3, 8, usize::MAX] {
+                let mut numbers = original.clone();
+                samplesort(&mut numbers, threads);
+                assert_eq!(numbers, expected);
+            }
+        }
+    }
+}
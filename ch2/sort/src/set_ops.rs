@@ -0,0 +1,377 @@
+//! 整列済み列の集合演算
+//! - `stable_merge_with_buf` と同じ2本のポインタによるマージで、同値は左（`a`）を先に採用する
+//! - 重複は多重集合として扱う（値 x が a に p 個、b に q 個あるとき、
+//!   和は max(p, q) 個、積は min(p, q) 個、差は max(p - q, 0) 個、対称差は |p - q| 個）
+//! - 同じ側が続く区間は指数探索（ギャロッピング）でまとめて進めるので、
+//!   長さが極端に違う入力では O(m log(n/m)) 回の比較で済む
+//! - 各演算にスライスを返す版と、遅延評価のイテレータ版がある
+
+/// `s[start..]` で `pred` が真である区間の終わりを指数探索で求める
+/// - `pred` は `s[start..]` 上で単調（真が続いた後に偽が続く）で、`pred(&s[start])` は真とする
+fn gallop<T>(s: &[T], start: usize, pred: impl Fn(&T) -> bool) -> usize {
+    let mut prev = start;
+    let mut step = 1;
+    loop {
+        let probe = prev + step;
+        if probe >= s.len() || !pred(&s[probe]) {
+            // 答えは (prev, probe] にある
+            let hi = probe.min(s.len());
+            return prev + 1 + s[prev + 1..hi].partition_point(&pred);
+        }
+        prev = probe;
+        step *= 2;
+    }
+}
+
+/// マージ中に現れる区間
+enum Run<'a, T> {
+    /// `a` にだけある値の区間
+    Left(&'a [T]),
+    /// `b` にだけある値の区間
+    Right(&'a [T]),
+    /// 両方にある同じ値の区間（`a` 側, `b` 側）
+    Both(&'a [T], &'a [T]),
+}
+
+/// 2つの整列済みスライスを区間ごとにマージする
+struct MergeRuns<'a, T> {
+    a: &'a [T],
+    b: &'a [T],
+    i: usize,
+    j: usize,
+}
+
+impl<'a, T: Ord> MergeRuns<'a, T> {
+    fn new(a: &'a [T], b: &'a [T]) -> Self {
+        Self { a, b, i: 0, j: 0 }
+    }
+}
+
+impl<'a, T: Ord> Iterator for MergeRuns<'a, T> {
+    type Item = Run<'a, T>;
+
+    fn next(&mut self) -> Option<Run<'a, T>> {
+        let (a, b, i, j) = (self.a, self.b, self.i, self.j);
+        // 片方が尽きたら残りを丸ごと返す
+        if i == a.len() {
+            if j == b.len() {
+                return None;
+            }
+            self.j = b.len();
+            return Some(Run::Right(&b[j..]));
+        }
+        if j == b.len() {
+            self.i = a.len();
+            return Some(Run::Left(&a[i..]));
+        }
+
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => {
+                self.i = gallop(a, i, |x| x < &b[j]);
+                Some(Run::Left(&a[i..self.i]))
+            }
+            std::cmp::Ordering::Greater => {
+                self.j = gallop(b, j, |x| x < &a[i]);
+                Some(Run::Right(&b[j..self.j]))
+            }
+            std::cmp::Ordering::Equal => {
+                self.i = gallop(a, i, |x| x <= &a[i]);
+                self.j = gallop(b, j, |x| x <= &b[j]);
+                Some(Run::Both(&a[i..self.i], &b[j..self.j]))
+            }
+        }
+    }
+}
+
+/// 区間を出力するスライス（高々2つ）に変換する関数
+type RunOp<'a, T> = fn(Run<'a, T>) -> [&'a [T]; 2];
+
+fn union_op<'a, T>(run: Run<'a, T>) -> [&'a [T]; 2] {
+    match run {
+        Run::Left(r) | Run::Right(r) => [r, &[]],
+        Run::Both(ra, rb) => [ra, &rb[ra.len().min(rb.len())..]],
+    }
+}
+
+fn intersection_op<'a, T>(run: Run<'a, T>) -> [&'a [T]; 2] {
+    match run {
+        Run::Left(_) | Run::Right(_) => [&[], &[]],
+        Run::Both(ra, rb) => [&ra[..ra.len().min(rb.len())], &[]],
+    }
+}
+
+fn difference_op<'a, T>(run: Run<'a, T>) -> [&'a [T]; 2] {
+    match run {
+        Run::Left(r) => [r, &[]],
+        Run::Right(_) => [&[], &[]],
+        Run::Both(ra, rb) => [&ra[ra.len().min(rb.len())..], &[]],
+    }
+}
+
+fn symmetric_difference_op<'a, T>(run: Run<'a, T>) -> [&'a [T]; 2] {
+    match run {
+        Run::Left(r) | Run::Right(r) => [r, &[]],
+        Run::Both(ra, rb) => [&ra[ra.len().min(rb.len())..], &rb[ra.len().min(rb.len())..]],
+    }
+}
+
+/// 集合演算の遅延イテレータ
+pub struct SetOp<'a, T> {
+    runs: MergeRuns<'a, T>,
+    op: RunOp<'a, T>,
+    pending: [&'a [T]; 2],
+}
+
+impl<'a, T: Ord> SetOp<'a, T> {
+    fn new(a: &'a [T], b: &'a [T], op: RunOp<'a, T>) -> Self {
+        Self {
+            runs: MergeRuns::new(a, b),
+            op,
+            pending: [&[], &[]],
+        }
+    }
+
+    /// 残りを区間ごとに `out` へ書き出す
+    fn extend_into(self, out: &mut Vec<T>)
+    where
+        T: Clone,
+    {
+        for piece in self.pending {
+            out.extend_from_slice(piece);
+        }
+        for run in self.runs {
+            for piece in (self.op)(run) {
+                out.extend_from_slice(piece);
+            }
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for SetOp<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            for piece in self.pending.iter_mut() {
+                if let Some((first, rest)) = piece.split_first() {
+                    *piece = rest;
+                    return Some(first);
+                }
+            }
+            self.pending = (self.op)(self.runs.next()?);
+        }
+    }
+}
+
+pub fn union_iter<'a, T: Ord>(a: &'a [T], b: &'a [T]) -> SetOp<'a, T> {
+    SetOp::new(a, b, union_op)
+}
+
+pub fn intersection_iter<'a, T: Ord>(a: &'a [T], b: &'a [T]) -> SetOp<'a, T> {
+    SetOp::new(a, b, intersection_op)
+}
+
+pub fn difference_iter<'a, T: Ord>(a: &'a [T], b: &'a [T]) -> SetOp<'a, T> {
+    SetOp::new(a, b, difference_op)
+}
+
+pub fn symmetric_difference_iter<'a, T: Ord>(a: &'a [T], b: &'a [T]) -> SetOp<'a, T> {
+    SetOp::new(a, b, symmetric_difference_op)
+}
+
+fn collect<T: Ord + Clone>(op: SetOp<'_, T>, capacity: usize) -> Vec<T> {
+    let mut out = Vec::with_capacity(capacity);
+    op.extend_into(&mut out);
+    out
+}
+
+/// 和集合
+pub fn union<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    collect(union_iter(a, b), a.len() + b.len())
+}
+
+/// 積集合
+pub fn intersection<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    collect(intersection_iter(a, b), a.len().min(b.len()))
+}
+
+/// 差集合 a \ b
+pub fn difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    collect(difference_iter(a, b), a.len())
+}
+
+/// 対称差
+pub fn symmetric_difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    collect(symmetric_difference_iter(a, b), a.len() + b.len())
+}
+
+/// 値ごとに (値, a での個数, b での個数) を昇順に返す遅延イテレータ
+pub struct MergeCounts<'a, T> {
+    a: &'a [T],
+    b: &'a [T],
+    i: usize,
+    j: usize,
+}
+
+impl<'a, T: Ord> Iterator for MergeCounts<'a, T> {
+    type Item = (&'a T, usize, usize);
+
+    fn next(&mut self) -> Option<(&'a T, usize, usize)> {
+        let (a, b, i, j) = (self.a, self.b, self.i, self.j);
+        // 小さい方の値を取り、両側で同じ値の区間を指数探索で数える
+        let value = match (a.get(i), b.get(j)) {
+            (None, None) => return None,
+            (Some(x), None) => x,
+            (None, Some(y)) => y,
+            (Some(x), Some(y)) => x.min(y),
+        };
+        if a.get(i) == Some(value) {
+            self.i = gallop(a, i, |x| x <= value);
+        }
+        if b.get(j) == Some(value) {
+            self.j = gallop(b, j, |x| x <= value);
+        }
+        Some((value, self.i - i, self.j - j))
+    }
+}
+
+pub fn merge_counts_iter<'a, T: Ord>(a: &'a [T], b: &'a [T]) -> MergeCounts<'a, T> {
+    MergeCounts { a, b, i: 0, j: 0 }
+}
+
+/// 多重集合のマージ（値ごとに a と b での個数を数える）
+pub fn merge_counts<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<(T, usize, usize)> {
+    merge_counts_iter(a, b)
+        .map(|(value, p, q)| (value.clone(), p, q))
+        .collect()
+}
+
+/// 整列済みスライスの重複を除く遅延イテレータ
+pub struct DedupSorted<'a, T> {
+    s: &'a [T],
+    i: usize,
+}
+
+impl<'a, T: Ord> Iterator for DedupSorted<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let value = self.s.get(self.i)?;
+        self.i = gallop(self.s, self.i, |x| x <= value);
+        Some(value)
+    }
+}
+
+pub fn dedup_sorted_iter<T: Ord>(s: &[T]) -> DedupSorted<'_, T> {
+    DedupSorted { s, i: 0 }
+}
+
+/// 整列済みスライスの重複除去
+pub fn dedup_sorted<T: Ord + Clone>(s: &[T]) -> Vec<T> {
+    dedup_sorted_iter(s).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::collections::BTreeMap;
+
+    fn counts(s: &[i32]) -> BTreeMap<i32, usize> {
+        let mut m = BTreeMap::new();
+        for &x in s {
+            *m.entry(x).or_insert(0) += 1;
+        }
+        m
+    }
+
+    /// 個数の規則から多重集合演算の答えを作る
+    fn oracle(a: &[i32], b: &[i32], rule: impl Fn(usize, usize) -> usize) -> Vec<i32> {
+        let (ca, cb) = (counts(a), counts(b));
+        let keys: std::collections::BTreeSet<i32> = ca.keys().chain(cb.keys()).copied().collect();
+        keys.into_iter()
+            .flat_map(|k| {
+                let n = rule(*ca.get(&k).unwrap_or(&0), *cb.get(&k).unwrap_or(&0));
+                std::iter::repeat_n(k, n)
+            })
+            .collect()
+    }
+
+    fn random_sorted(rng: &mut impl Rng, n: usize, max: i32) -> Vec<i32> {
+        let mut v: Vec<i32> = (0..n).map(|_| rng.gen_range(0..max)).collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn test_against_oracle() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let (n, m) = (rng.gen_range(0..40), rng.gen_range(0..40));
+            let a = random_sorted(&mut rng, n, 15);
+            let b = random_sorted(&mut rng, m, 15);
+
+            assert_eq!(union(&a, &b), oracle(&a, &b, |p, q| p.max(q)));
+            assert_eq!(intersection(&a, &b), oracle(&a, &b, |p, q| p.min(q)));
+            assert_eq!(
+                difference(&a, &b),
+                oracle(&a, &b, |p, q| p.saturating_sub(q))
+            );
+            assert_eq!(
+                symmetric_difference(&a, &b),
+                oracle(&a, &b, |p, q| p.abs_diff(q))
+            );
+
+            // イテレータ版もスライス版と一致する
+            assert_eq!(
+                union_iter(&a, &b).copied().collect::<Vec<_>>(),
+                union(&a, &b)
+            );
+            assert_eq!(
+                symmetric_difference_iter(&a, &b)
+                    .copied()
+                    .collect::<Vec<_>>(),
+                symmetric_difference(&a, &b)
+            );
+
+            let expected: Vec<(i32, usize, usize)> = oracle(&a, &b, |p, q| p.max(q).min(1))
+                .into_iter()
+                .map(|k| {
+                    (
+                        k,
+                        counts(&a).get(&k).copied().unwrap_or(0),
+                        counts(&b).get(&k).copied().unwrap_or(0),
+                    )
+                })
+                .collect();
+            assert_eq!(merge_counts(&a, &b), expected);
+            assert_eq!(dedup_sorted(&a), oracle(&a, &a, |p, _| p.min(1)));
+        }
+    }
+
+    #[test]
+    fn test_unbalanced_inputs() {
+        let big: Vec<i32> = (0..100_000).map(|x| x * 2).collect();
+        let small = vec![-1, 10, 11, 99_998, 300_000];
+        assert_eq!(intersection(&small, &big), vec![10, 99_998]);
+        assert_eq!(difference(&small, &big), vec![-1, 11, 300_000]);
+        assert_eq!(union(&small, &big).len(), big.len() + 3);
+    }
+
+    #[test]
+    fn test_iterator_stops_early() {
+        let a: Vec<i32> = (0..1000).collect();
+        let b: Vec<i32> = (500..1500).collect();
+        let first: Vec<i32> = intersection_iter(&a, &b).take(3).copied().collect();
+        assert_eq!(first, vec![500, 501, 502]);
+    }
+
+    #[test]
+    fn test_union_prefers_left_elements() {
+        // 値は同じでも、どちらのスライスの要素が使われたかをアドレスで見分ける
+        let a = [1, 2];
+        let b = [2, 3];
+        let merged: Vec<*const i32> = union_iter(&a, &b).map(|x| x as *const i32).collect();
+        assert_eq!(merged, vec![&a[0] as *const i32, &a[1], &b[1]]);
+    }
+}
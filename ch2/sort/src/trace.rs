@@ -0,0 +1,397 @@
+//! ソートの実行トレース
+//! - ソート関数に観測者（`SortObserver`）を渡すと、比較や移動のたびにイベントが通知される
+//! - 記録したイベント列から、端末用のコマ送りアニメーションと SVG アニメーションを作る
+
+use std::fmt::Write;
+
+/// 要素の置き場所
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// 元の配列の添字
+    Arr(usize),
+    /// マージ用バッファの添字（元の配列と同じ添字で対応）
+    Buf(usize),
+    /// 二分挿入ソートで挿入する値を退避する一時変数
+    Tmp,
+}
+
+/// ソート中に発生するイベント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortEvent {
+    /// arr[i] と arr[j] を比較した
+    Compare(usize, usize),
+    /// 値を src から dst へコピーした
+    Move(Slot, Slot),
+    /// [start..end) の二分挿入ソートが終わった
+    BlockSorted(usize, usize),
+    /// 幅 width の整列済み区間同士のマージを始めた
+    MergeLevel(usize),
+}
+
+/// ソートの観測者
+pub trait SortObserver {
+    fn on_event(&mut self, event: SortEvent);
+}
+
+/// 何もしない観測者
+/// - 単相化で呼び出しごと消えるので、ベンチマークの速度には影響しない
+pub struct NoopObserver;
+
+impl SortObserver for NoopObserver {
+    #[inline(always)]
+    fn on_event(&mut self, _event: SortEvent) {}
+}
+
+/// イベントをそのまま記録する
+impl SortObserver for Vec<SortEvent> {
+    fn on_event(&mut self, event: SortEvent) {
+        self.push(event);
+    }
+}
+
+/// イベントを再生するための配列・バッファ・一時変数の状態
+struct TraceState {
+    arr: Vec<i32>,
+    buf: Vec<Option<i32>>,
+    tmp: Option<i32>,
+}
+
+impl TraceState {
+    fn new(initial: &[i32]) -> Self {
+        Self {
+            arr: initial.to_vec(),
+            buf: vec![None; initial.len()],
+            tmp: None,
+        }
+    }
+
+    fn get(&self, slot: Slot) -> Option<i32> {
+        match slot {
+            Slot::Arr(i) => Some(self.arr[i]),
+            Slot::Buf(i) => self.buf[i],
+            Slot::Tmp => self.tmp,
+        }
+    }
+
+    fn apply(&mut self, event: SortEvent) {
+        if let SortEvent::Move(src, dst) = event {
+            let value = self.get(src).expect("空の場所からは移動できない");
+            match dst {
+                Slot::Arr(i) => self.arr[i] = value,
+                Slot::Buf(i) => self.buf[i] = Some(value),
+                Slot::Tmp => self.tmp = Some(value),
+            }
+        }
+    }
+}
+
+fn describe(event: SortEvent) -> String {
+    fn slot_name(slot: Slot) -> String {
+        match slot {
+            Slot::Arr(i) => format!("arr[{}]", i),
+            Slot::Buf(i) => format!("buf[{}]", i),
+            Slot::Tmp => "tmp".to_string(),
+        }
+    }
+    match event {
+        SortEvent::Compare(i, j) => format!("比較 arr[{}] と arr[{}]", i, j),
+        SortEvent::Move(src, dst) => format!("移動 {} -> {}", slot_name(src), slot_name(dst)),
+        SortEvent::BlockSorted(start, end) => format!("区間 [{}..{}) を挿入ソート済み", start, end),
+        SortEvent::MergeLevel(width) => format!("幅 {} の区間同士をマージ開始", width),
+    }
+}
+
+/// イベントで強調表示する arr の添字（比較対象, 書き込み先）
+fn highlights(event: SortEvent) -> (Vec<usize>, Option<Slot>) {
+    match event {
+        SortEvent::Compare(i, j) => (vec![i, j], None),
+        SortEvent::Move(_, dst) => (vec![], Some(dst)),
+        SortEvent::BlockSorted(..) | SortEvent::MergeLevel(_) => (vec![], None),
+    }
+}
+
+/// 値を 1..=height の棒の高さに変換する
+fn bar_level(value: i32, min: i32, max: i32, height: usize) -> usize {
+    let span = (max as i64 - min as i64).max(1);
+    ((value as i64 - min as i64) * (height as i64 - 1) / span) as usize + 1
+}
+
+const TEXT_HEIGHT: usize = 8;
+
+/// 棒の高さは途中で値が重複しても揺れないよう、初期配列の最小値・最大値 `(min, max)` で決める
+fn render_text_frame(
+    state: &TraceState,
+    (min, max): (i32, i32),
+    header: &str,
+    event: Option<SortEvent>,
+) -> String {
+    let (compared, written) = event.map(highlights).unwrap_or_default();
+
+    let mut frame = String::new();
+    writeln!(frame, "{}", header).unwrap();
+
+    // 棒グラフ（上の行から描く）
+    for row in (1..=TEXT_HEIGHT).rev() {
+        let line: String = state
+            .arr
+            .iter()
+            .map(|&v| {
+                if bar_level(v, min, max, TEXT_HEIGHT) >= row {
+                    "█ "
+                } else {
+                    "  "
+                }
+            })
+            .collect();
+        writeln!(frame, "{}", line.trim_end()).unwrap();
+    }
+
+    // 比較は ^、書き込みは * で印をつける
+    let marks: String = (0..state.arr.len())
+        .map(|i| {
+            if written == Some(Slot::Arr(i)) {
+                "* "
+            } else if compared.contains(&i) {
+                "^ "
+            } else {
+                "  "
+            }
+        })
+        .collect();
+    writeln!(frame, "{}", marks.trim_end()).unwrap();
+
+    let arr: Vec<String> = state.arr.iter().map(|v| v.to_string()).collect();
+    writeln!(frame, "arr: {}", arr.join(" ")).unwrap();
+    let buf: Vec<String> = state
+        .buf
+        .iter()
+        .map(|v| v.map_or(".".to_string(), |v| v.to_string()))
+        .collect();
+    writeln!(frame, "buf: {}", buf.join(" ")).unwrap();
+    writeln!(
+        frame,
+        "tmp: {}",
+        state.tmp.map_or(".".to_string(), |v| v.to_string())
+    )
+    .unwrap();
+    frame
+}
+
+/// イベント列を端末向けのコマに変換する
+/// - 先頭のコマは初期状態で、以後イベント1つにつき1コマ
+pub fn render_text_frames(initial: &[i32], events: &[SortEvent]) -> Vec<String> {
+    let range = (
+        initial.iter().copied().min().unwrap_or(0),
+        initial.iter().copied().max().unwrap_or(0),
+    );
+    let mut state = TraceState::new(initial);
+    let mut frames = vec![render_text_frame(
+        &state,
+        range,
+        &format!("[{:>4}/{}] 初期状態", 0, events.len()),
+        None,
+    )];
+    for (step, &event) in events.iter().enumerate() {
+        state.apply(event);
+        let header = format!("[{:>4}/{}] {}", step + 1, events.len(), describe(event));
+        frames.push(render_text_frame(&state, range, &header, Some(event)));
+    }
+    frames
+}
+
+const SVG_BAR_WIDTH: usize = 20;
+const SVG_ARR_HEIGHT: usize = 200;
+const SVG_BUF_HEIGHT: usize = 100;
+const SVG_MARGIN: usize = 20;
+
+/// 1本の棒を、コマごとの高さと色を離散アニメーションさせる rect として書き出す
+fn write_svg_bar(
+    svg: &mut String,
+    x: usize,
+    base_y: usize,
+    heights: &[usize],
+    colors: &[&str],
+    dur_ms: usize,
+) {
+    let ys: Vec<String> = heights.iter().map(|h| (base_y - h).to_string()).collect();
+    let hs: Vec<String> = heights.iter().map(|h| h.to_string()).collect();
+    writeln!(
+        svg,
+        r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}">"#,
+        x,
+        ys[0],
+        SVG_BAR_WIDTH - 2,
+        hs[0],
+        colors[0]
+    )
+    .unwrap();
+    for (attr, values) in [
+        ("y", ys.join(";")),
+        ("height", hs.join(";")),
+        ("fill", colors.join(";")),
+    ] {
+        writeln!(
+            svg,
+            r#"    <animate attributeName="{}" values="{}" dur="{}ms" calcMode="discrete" repeatCount="indefinite"/>"#,
+            attr, values, dur_ms
+        )
+        .unwrap();
+    }
+    writeln!(svg, "  </rect>").unwrap();
+}
+
+/// イベント列を SVG の棒グラフアニメーションに変換する
+/// - 上段が arr、下段が buf で、1コマ `frame_ms` ミリ秒で再生する
+/// - 比較中の棒はオレンジ、書き込まれた棒は赤で表示する
+pub fn render_svg(initial: &[i32], events: &[SortEvent], frame_ms: usize) -> String {
+    let n = initial.len();
+    let min = initial.iter().copied().min().unwrap_or(0);
+    let max = initial.iter().copied().max().unwrap_or(0);
+
+    // コマごとの各棒の高さと色を集める
+    let frames = events.len() + 1;
+    let mut arr_heights = vec![Vec::with_capacity(frames); n];
+    let mut arr_colors = vec![Vec::with_capacity(frames); n];
+    let mut buf_heights = vec![Vec::with_capacity(frames); n];
+    let mut buf_colors = vec![Vec::with_capacity(frames); n];
+
+    let mut state = TraceState::new(initial);
+    for step in 0..frames {
+        let event = if step == 0 {
+            None
+        } else {
+            Some(events[step - 1])
+        };
+        if let Some(event) = event {
+            state.apply(event);
+        }
+        let (compared, written) = event.map(highlights).unwrap_or_default();
+        for i in 0..n {
+            arr_heights[i].push(bar_level(state.arr[i], min, max, SVG_ARR_HEIGHT));
+            arr_colors[i].push(if written == Some(Slot::Arr(i)) {
+                "crimson"
+            } else if compared.contains(&i) {
+                "orange"
+            } else {
+                "steelblue"
+            });
+            buf_heights[i].push(state.buf[i].map_or(0, |v| bar_level(v, min, max, SVG_BUF_HEIGHT)));
+            buf_colors[i].push(if written == Some(Slot::Buf(i)) {
+                "crimson"
+            } else {
+                "gray"
+            });
+        }
+    }
+
+    let width = n * SVG_BAR_WIDTH + 2 * SVG_MARGIN;
+    let arr_base = SVG_MARGIN + SVG_ARR_HEIGHT;
+    let buf_base = arr_base + SVG_MARGIN + SVG_BUF_HEIGHT;
+    let height = buf_base + SVG_MARGIN;
+    let dur_ms = frames * frame_ms;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        width, height, width, height
+    )
+    .unwrap();
+    writeln!(svg, r#"  <rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    for i in 0..n {
+        let x = SVG_MARGIN + i * SVG_BAR_WIDTH;
+        write_svg_bar(
+            &mut svg,
+            x,
+            arr_base,
+            &arr_heights[i],
+            &arr_colors[i],
+            dur_ms,
+        );
+        write_svg_bar(
+            &mut svg,
+            x,
+            buf_base,
+            &buf_heights[i],
+            &buf_colors[i],
+            dur_ms,
+        );
+    }
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hybrid_bottom_up_merge_sort_observed;
+
+    fn traced_sort(initial: &[i32], threshold: usize) -> (Vec<i32>, Vec<SortEvent>) {
+        let mut arr = initial.to_vec();
+        let mut events = Vec::new();
+        hybrid_bottom_up_merge_sort_observed(&mut arr, threshold, &mut events);
+        (arr, events)
+    }
+
+    #[test]
+    fn test_replay_reproduces_sorted_array() {
+        let initial = vec![
+            3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9, 3, 2, 3, 8, 4, 6, 2,
+        ];
+        let (sorted, events) = traced_sort(&initial, 4);
+
+        let mut state = TraceState::new(&initial);
+        for &event in &events {
+            state.apply(event);
+        }
+        assert_eq!(state.arr, sorted);
+    }
+
+    #[test]
+    fn test_events_contain_blocks_and_levels() {
+        let initial: Vec<i32> = (0..10).rev().collect();
+        let (_, events) = traced_sort(&initial, 4);
+
+        let blocks: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e, SortEvent::BlockSorted(..)))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                &SortEvent::BlockSorted(0, 4),
+                &SortEvent::BlockSorted(4, 8),
+                &SortEvent::BlockSorted(8, 10)
+            ]
+        );
+        let levels: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e, SortEvent::MergeLevel(_)))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![&SortEvent::MergeLevel(4), &SortEvent::MergeLevel(8)]
+        );
+    }
+
+    #[test]
+    fn test_render_text_frames() {
+        let initial = vec![2, 1];
+        let (_, events) = traced_sort(&initial, 2);
+        let frames = render_text_frames(&initial, &events);
+        assert_eq!(frames.len(), events.len() + 1);
+        assert!(frames[0].contains("arr: 2 1"));
+        assert!(frames.last().unwrap().contains("arr: 1 2"));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let initial = vec![5, 3, 8, 1];
+        let (_, events) = traced_sort(&initial, 2);
+        let svg = render_svg(&initial, &events, 100);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        // arr と buf で1要素につき2本の棒
+        assert_eq!(svg.matches("<rect x=").count(), 2 * initial.len());
+    }
+}
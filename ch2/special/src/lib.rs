@@ -0,0 +1,36 @@
+//! ch2 のクレートで共有する特殊関数
+
+/// 相補誤差関数（Numerical Recipes の erfcc、相対誤差 1.2e-7 未満）
+pub fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erfc() {
+        // erfc(0) = 1、erfc(1) = 0.1572992070…、erfc(-x) = 2 - erfc(x)
+        assert!((erfc(0.0) - 1.0).abs() < 1e-7);
+        assert!((erfc(1.0) - 0.157_299_207_050_285_1).abs() < 1e-7);
+        assert!((erfc(-1.0) - (2.0 - erfc(1.0))).abs() < 1e-12);
+        assert!(erfc(10.0) < 1e-40);
+    }
+}